@@ -0,0 +1,173 @@
+//! Provides `#[derive(FromCustomAttribute)]` for `climeta::schema::FromCustomAttribute`.
+//!
+//! The attribute type is specified with `#[climeta(attr = "Namespace.TypeName")]`. Fields are
+//! read from the positional (constructor) arguments in declaration order, except for fields marked
+//! with `#[climeta(named)]` or `#[climeta(named = "Name")]`, which are read from the named
+//! arguments (fields or properties) of the attribute. Without an explicit name, a `snake_case`
+//! field name is converted to `PascalCase`. Named arguments of type `Option<T>` are optional.
+//!
+//! ```ignore
+//! #[derive(FromCustomAttribute)]
+//! #[climeta(attr = "Windows.Foundation.Metadata.VersionAttribute")]
+//! struct Version(u32);
+//! ```
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, LitStr};
+
+#[proc_macro_derive(FromCustomAttribute, attributes(climeta))]
+pub fn derive_from_custom_attribute(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into()
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let attr_name = parse_type_attr(&input.attrs)?
+        .ok_or_else(|| Error::new(Span::call_site(), "missing #[climeta(attr = \"Namespace.TypeName\")]"))?;
+    let full_name = attr_name.value();
+    let (namespace, name) = match full_name.rfind('.') {
+        Some(dot) => (&full_name[..dot], &full_name[dot+1 ..]),
+        None => ("", &full_name[..])
+    };
+
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => return Err(Error::new(Span::call_site(), "FromCustomAttribute can only be derived for structs"))
+    };
+
+    if input.generics.type_params().next().is_some() || input.generics.const_params().next().is_some() {
+        return Err(Error::new_spanned(&input.generics, "FromCustomAttribute can not be derived for generic structs"));
+    }
+    let lifetime = match input.generics.lifetimes().count() {
+        0 => None,
+        1 => Some(input.generics.lifetimes().next().unwrap().lifetime.clone()),
+        _ => return Err(Error::new_spanned(&input.generics, "FromCustomAttribute can only be derived for structs with at most one lifetime"))
+    };
+
+    let mut reads = Vec::new();
+    let mut vars = Vec::new();
+    let mut positional = 0usize;
+    for (i, field) in fields.iter().enumerate() {
+        let var = format_ident!("__field{}", i);
+        let ty = &field.ty;
+        let read = match parse_field_attr(&field.attrs)? {
+            Some(named) => {
+                let arg_name = match named {
+                    Some(n) => n.value(),
+                    None => match field.ident {
+                        Some(ref ident) => pascal_case(&ident.to_string()),
+                        None => return Err(Error::new_spanned(field, "named arguments of tuple structs require an explicit name"))
+                    }
+                };
+                let msg = format!("{}: named argument `{}` is missing or has an unexpected type", full_name, arg_name);
+                quote! {
+                    let #var: #ty = sig.named_arg::<#ty>(#arg_name)
+                        .ok_or_else(|| ::climeta::DecodeError::from(#msg))?;
+                }
+            },
+            None => {
+                let index = positional;
+                positional += 1;
+                let msg_missing = format!("{}: missing constructor argument {}", full_name, index);
+                let msg_type = format!("{}: constructor argument {} has an unexpected type", full_name, index);
+                quote! {
+                    if sig.fixed_args().len() <= #index {
+                        return Err(::climeta::DecodeError::from(#msg_missing));
+                    }
+                    let #var: #ty = sig.fixed_arg::<#ty>(#index)
+                        .ok_or_else(|| ::climeta::DecodeError::from(#msg_type))?;
+                }
+            }
+        };
+        reads.push(read);
+        vars.push(var);
+    }
+
+    let construct = match fields {
+        Fields::Named(_) => {
+            let idents = fields.iter().map(|f| f.ident.as_ref().unwrap());
+            quote! { Self { #(#idents: #vars),* } }
+        },
+        Fields::Unnamed(_) => quote! { Self(#(#vars),*) },
+        Fields::Unit => quote! { Self }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, self_type, trait_lifetime) = match lifetime {
+        Some(lt) => (quote! { <#lt> }, quote! { #ident<#lt> }, quote! { #lt }),
+        None => (quote! { <'db> }, quote! { #ident }, quote! { 'db })
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::climeta::schema::FromCustomAttribute<#trait_lifetime> for #self_type {
+            const TYPE_NAMESPACE: &'static str = #namespace;
+            const TYPE_NAME: &'static str = #name;
+
+            fn from_custom_attribute(sig: &::climeta::schema::CustomAttributeSig<#trait_lifetime>) -> ::std::result::Result<Self, ::climeta::DecodeError> {
+                #(#reads)*
+                Ok(#construct)
+            }
+        }
+    })
+}
+
+// Parses `#[climeta(attr = "...")]` on the struct
+fn parse_type_attr(attrs: &[Attribute]) -> Result<Option<LitStr>, Error> {
+    let mut result = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("climeta")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("attr") {
+                result = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported climeta attribute, expected `attr`"))
+            }
+        })?;
+    }
+    Ok(result)
+}
+
+// Parses `#[climeta(named)]` or `#[climeta(named = "...")]` on a field
+fn parse_field_attr(attrs: &[Attribute]) -> Result<Option<Option<LitStr>>, Error> {
+    let mut result = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("climeta")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("named") {
+                result = Some(if meta.input.peek(syn::Token![=]) { Some(meta.value()?.parse()?) } else { None });
+                Ok(())
+            } else {
+                Err(meta.error("unsupported climeta attribute, expected `named`"))
+            }
+        })?;
+    }
+    Ok(result)
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split('_').filter(|part| !part.is_empty()).map(|part| {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new()
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pascal_case;
+
+    #[test]
+    fn test_pascal_case() {
+        assert_eq!(pascal_case("allow_multiple"), "AllowMultiple");
+        assert_eq!(pascal_case("inherited"), "Inherited");
+        assert_eq!(pascal_case("_x"), "X");
+    }
+}
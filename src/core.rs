@@ -4,6 +4,8 @@ pub(crate) mod db;
 pub(crate) mod table;
 pub(crate) mod pe;
 pub(crate) mod columns;
+#[cfg(test)]
+pub(crate) mod builder;
 
 pub(crate) trait BitView {
     fn get_bit(self, bit: usize) -> bool;
@@ -0,0 +1,313 @@
+// Assembles minimal PE images with CLI metadata for unit tests. All heap and table
+// indexes are 2 bytes wide, so tables must stay below 2^(16 - tag bits) rows.
+
+use std::collections::BTreeMap;
+
+const SECTION_RVA: u32 = 0x2000;
+const SECTION_OFFSET: usize = 0x200;
+const CLI_HEADER_SIZE: usize = 72;
+
+// ECMA-335, II.22 (column sizes with 2-byte heap and table indexes)
+fn column_sizes(table: u8) -> &'static [usize] {
+    match table {
+        0x00 => &[2, 2, 2, 2, 2], // Module
+        0x01 => &[2, 2, 2], // TypeRef
+        0x02 => &[4, 2, 2, 2, 2, 2], // TypeDef
+        0x04 => &[2, 2, 2], // Field
+        0x06 => &[4, 2, 2, 2, 2, 2], // MethodDef
+        0x08 => &[2, 2, 2], // Param
+        0x09 => &[2, 2], // InterfaceImpl
+        0x0a => &[2, 2, 2], // MemberRef
+        0x0b => &[2, 2, 2], // Constant
+        0x0c => &[2, 2, 2], // CustomAttribute
+        0x0d => &[2, 2], // FieldMarshal
+        0x0e => &[2, 2, 2], // DeclSecurity
+        0x0f => &[2, 4, 2], // ClassLayout
+        0x10 => &[4, 2], // FieldLayout
+        0x11 => &[2], // StandAloneSig
+        0x12 => &[2, 2], // EventMap
+        0x14 => &[2, 2, 2], // Event
+        0x15 => &[2, 2], // PropertyMap
+        0x17 => &[2, 2, 2], // Property
+        0x18 => &[2, 2, 2], // MethodSemantics
+        0x19 => &[2, 2, 2], // MethodImpl
+        0x1a => &[2], // ModuleRef
+        0x1b => &[2], // TypeSpec
+        0x1c => &[2, 2, 2, 2], // ImplMap
+        0x1d => &[4, 2], // FieldRVA
+        0x20 => &[4, 8, 4, 2, 2, 2], // Assembly
+        0x21 => &[4], // AssemblyProcessor
+        0x22 => &[4, 4, 4], // AssemblyOS
+        0x23 => &[8, 4, 2, 2, 2, 2], // AssemblyRef
+        0x24 => &[4, 2], // AssemblyRefProcessor
+        0x25 => &[4, 4, 4, 2], // AssemblyRefOS
+        0x26 => &[4, 2, 2], // File
+        0x27 => &[4, 4, 2, 2, 2], // ExportedType
+        0x28 => &[4, 4, 2, 2], // ManifestResource
+        0x29 => &[2, 2], // NestedClass
+        0x2a => &[2, 2, 2, 2], // GenericParam
+        0x2b => &[2, 2], // MethodSpec
+        0x2c => &[2, 2], // GenericParamConstraint
+        _ => panic!("unknown metadata table 0x{:02x}", table)
+    }
+}
+
+pub(crate) const CLASS: u64 = 0x0010_0001; // public, before field init
+pub(crate) const INTERFACE: u64 = 0x0000_00a1; // public, interface, abstract
+
+/// Returns the value of a coded index column (ECMA-335, II.24.2.6) for a 1-based row.
+pub(crate) fn coded(row: u32, tag: u8, tag_bits: u8) -> u64 {
+    ((row as u64) << tag_bits) | tag as u64
+}
+
+pub(crate) struct ImageBuilder {
+    strings: Vec<u8>,
+    blobs: Vec<u8>,
+    user_strings: Vec<u8>,
+    data: Vec<u8>,
+    tables: BTreeMap<u8, Vec<Vec<u64>>>,
+}
+
+impl ImageBuilder {
+    pub(crate) fn new() -> ImageBuilder {
+        ImageBuilder {
+            strings: vec![0],
+            blobs: vec![0],
+            user_strings: vec![0],
+            data: Vec::new(),
+            tables: BTreeMap::new()
+        }
+    }
+
+    /// Adds a string to the `#Strings` heap and returns its index.
+    pub(crate) fn string(&mut self, s: &str) -> u32 {
+        if s.is_empty() {
+            return 0;
+        }
+        let index = self.strings.len() as u32;
+        self.strings.extend_from_slice(s.as_bytes());
+        self.strings.push(0);
+        index
+    }
+
+    /// Adds a blob to the `#Blob` heap and returns its index.
+    pub(crate) fn blob(&mut self, blob: &[u8]) -> u32 {
+        let index = self.blobs.len() as u32;
+        push_blob(&mut self.blobs, blob);
+        index
+    }
+
+    /// Adds a string to the `#US` heap and returns its index.
+    pub(crate) fn user_string(&mut self, units: &[u16]) -> u32 {
+        let mut blob: Vec<u8> = units.iter().flat_map(|u| u.to_le_bytes().to_vec()).collect();
+        blob.push(0);
+        let index = self.user_strings.len() as u32;
+        push_blob(&mut self.user_strings, &blob);
+        index
+    }
+
+    /// Adds raw data to the image and returns its RVA.
+    pub(crate) fn data(&mut self, data: &[u8]) -> u32 {
+        self.data.resize(align(self.data.len(), 4), 0);
+        let rva = SECTION_RVA + (CLI_HEADER_SIZE + self.data.len()) as u32;
+        self.data.extend_from_slice(data);
+        rva
+    }
+
+    /// Appends a row to a table and returns its 1-based index. Rows of sorted tables
+    /// must be added in key order.
+    pub(crate) fn row(&mut self, table: u8, values: &[u64]) -> u32 {
+        assert_eq!(column_sizes(table).len(), values.len(), "wrong column count for table 0x{:02x}", table);
+        let rows = self.tables.entry(table).or_default();
+        rows.push(values.to_vec());
+        rows.len() as u32
+    }
+
+    /// Adds the `<Module>` type, which must be the first row of the TypeDef table.
+    pub(crate) fn module_type(&mut self) -> u32 {
+        self.type_def(0, "", "<Module>", 0, 1, 1)
+    }
+
+    /// Adds a TypeDef row. A type owns the fields and methods from `field_list` and
+    /// `method_list` up to the lists of the next type.
+    pub(crate) fn type_def(&mut self, flags: u64, namespace: &str, name: &str, extends: u64, field_list: u32, method_list: u32) -> u32 {
+        let name = self.string(name) as u64;
+        let namespace = self.string(namespace) as u64;
+        self.row(0x02, &[flags, name, namespace, extends, field_list as u64, method_list as u64])
+    }
+
+    /// Adds a TypeRef row without a resolution scope.
+    pub(crate) fn type_ref(&mut self, namespace: &str, name: &str) -> u32 {
+        let name = self.string(name) as u64;
+        let namespace = self.string(namespace) as u64;
+        self.row(0x01, &[0, name, namespace])
+    }
+
+    pub(crate) fn field(&mut self, flags: u64, name: &str, signature: &[u8]) -> u32 {
+        let name = self.string(name) as u64;
+        let signature = self.blob(signature) as u64;
+        self.row(0x04, &[flags, name, signature])
+    }
+
+    /// Adds a MethodDef row without a body or parameters.
+    pub(crate) fn method(&mut self, flags: u64, name: &str, signature: &[u8]) -> u32 {
+        let name = self.string(name) as u64;
+        let signature = self.blob(signature) as u64;
+        self.row(0x06, &[0, 0, flags, name, signature, 1])
+    }
+
+    pub(crate) fn build(&self) -> Vec<u8> {
+        // ECMA-335, II.24.2.6
+        let mut tables = Vec::new();
+        tables.extend_from_slice(&0u32.to_le_bytes());
+        tables.extend_from_slice(&[2, 0, 0, 1]); // version 2.0, 2-byte heap indexes
+        let valid = self.tables.keys().fold(0u64, |valid, &table| valid | 1 << table);
+        tables.extend_from_slice(&valid.to_le_bytes());
+        tables.extend_from_slice(&0u64.to_le_bytes());
+        for rows in self.tables.values() {
+            tables.extend_from_slice(&(rows.len() as u32).to_le_bytes());
+        }
+        for (&table, rows) in &self.tables {
+            for row in rows {
+                for (&value, &size) in row.iter().zip(column_sizes(table)) {
+                    tables.extend_from_slice(&value.to_le_bytes()[..size]);
+                }
+            }
+        }
+
+        let streams: [(&str, &[u8]); 5] = [
+            ("#~", &tables),
+            ("#Strings", &self.strings),
+            ("#US", &self.user_strings),
+            ("#GUID", &[]),
+            ("#Blob", &self.blobs)
+        ];
+
+        // ECMA-335, II.24.2.1
+        let version = b"v4.0.30319\0\0";
+        let mut header_size = 16 + version.len() + 4;
+        for (name, _) in &streams {
+            header_size += 8 + (name.len() + 4) / 4 * 4;
+        }
+        let mut metadata = Vec::new();
+        metadata.extend_from_slice(&0x424a_5342u32.to_le_bytes());
+        metadata.extend_from_slice(&[1, 0, 1, 0, 0, 0, 0, 0]);
+        metadata.extend_from_slice(&(version.len() as u32).to_le_bytes());
+        metadata.extend_from_slice(version);
+        metadata.extend_from_slice(&0u16.to_le_bytes());
+        metadata.extend_from_slice(&(streams.len() as u16).to_le_bytes());
+        let mut offset = align(header_size, 8);
+        for (name, stream) in &streams {
+            metadata.extend_from_slice(&(offset as u32).to_le_bytes());
+            metadata.extend_from_slice(&(stream.len() as u32).to_le_bytes());
+            let mut name = name.as_bytes().to_vec();
+            name.resize((name.len() + 4) / 4 * 4, 0);
+            metadata.extend_from_slice(&name);
+            offset = align(offset + stream.len(), 8);
+        }
+        for (_, stream) in &streams {
+            metadata.resize(align(metadata.len(), 8), 0);
+            metadata.extend_from_slice(stream);
+        }
+
+        let metadata_offset = align(CLI_HEADER_SIZE + self.data.len(), 8);
+        let section_size = metadata_offset + metadata.len();
+
+        let mut image = vec![0u8; SECTION_OFFSET + section_size];
+
+        // DOS header with e_lfanew
+        image[0..2].copy_from_slice(b"MZ");
+        image[0x3c..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+
+        // PE signature and file header
+        image[0x80..0x84].copy_from_slice(b"PE\0\0");
+        put_u16(&mut image, 0x84, 0x14c); // Machine (i386)
+        put_u16(&mut image, 0x86, 1); // NumberOfSections
+        put_u16(&mut image, 0x94, 224); // SizeOfOptionalHeader
+
+        // PE32 optional header
+        let optional = 0x98;
+        put_u16(&mut image, optional, 0x10b);
+        put_u32(&mut image, optional + 92, 16); // NumberOfRvaAndSizes
+        put_u32(&mut image, optional + 96 + 14 * 8, SECTION_RVA); // CLI header directory
+        put_u32(&mut image, optional + 96 + 14 * 8 + 4, CLI_HEADER_SIZE as u32);
+
+        // section header
+        let section = optional + 224;
+        image[section..section + 5].copy_from_slice(b".text");
+        put_u32(&mut image, section + 8, section_size as u32); // VirtualSize
+        put_u32(&mut image, section + 12, SECTION_RVA);
+        put_u32(&mut image, section + 16, section_size as u32); // SizeOfRawData
+        put_u32(&mut image, section + 20, SECTION_OFFSET as u32);
+
+        // CLI header (ECMA-335, II.25.3.3)
+        let cli = SECTION_OFFSET;
+        put_u32(&mut image, cli, CLI_HEADER_SIZE as u32);
+        put_u16(&mut image, cli + 4, 2);
+        put_u16(&mut image, cli + 6, 5);
+        put_u32(&mut image, cli + 8, SECTION_RVA + metadata_offset as u32);
+        put_u32(&mut image, cli + 12, metadata.len() as u32);
+        put_u32(&mut image, cli + 16, 1); // COMIMAGE_FLAGS_ILONLY
+
+        let data = SECTION_OFFSET + CLI_HEADER_SIZE;
+        image[data..data + self.data.len()].copy_from_slice(&self.data);
+        let metadata_start = SECTION_OFFSET + metadata_offset;
+        image[metadata_start..].copy_from_slice(&metadata);
+        image
+    }
+}
+
+fn push_blob(heap: &mut Vec<u8>, blob: &[u8]) {
+    // ECMA-335, II.24.2.4
+    let len = blob.len();
+    if len < 0x80 {
+        heap.push(len as u8);
+    } else if len < 0x4000 {
+        heap.extend_from_slice(&(0x8000 | len as u16).to_be_bytes());
+    } else {
+        heap.extend_from_slice(&(0xc000_0000 | len as u32).to_be_bytes());
+    }
+    heap.extend_from_slice(blob);
+}
+
+fn align(value: usize, alignment: usize) -> usize {
+    match value % alignment {
+        0 => value,
+        rem => value + alignment - rem
+    }
+}
+
+fn put_u16(image: &mut [u8], offset: usize, value: u16) {
+    image[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(image: &mut [u8], offset: usize, value: u32) {
+    image[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+// A`1, B`1 : A<!0>, C : B<int32>, IFoo (implemented by B`1), X : Y, Y : X,
+// and MarkerAttribute, which is applied to C
+pub(crate) fn hierarchy_image() -> Vec<u8> {
+    let mut builder = ImageBuilder::new();
+    builder.module_type();
+    let a = builder.type_def(CLASS, "N", "A`1", 0, 1, 1);
+    let b = builder.type_def(CLASS, "N", "B`1", coded(1, 2, 2), 1, 1);
+    let c = builder.type_def(CLASS, "N", "C", coded(2, 2, 2), 1, 1);
+    let ifoo = builder.type_def(INTERFACE, "N", "IFoo", 0, 1, 1);
+    builder.type_def(CLASS, "N", "X", coded(7, 0, 2), 1, 1);
+    builder.type_def(CLASS, "N", "Y", coded(6, 0, 2), 1, 1);
+    builder.type_def(CLASS, "N", "MarkerAttribute", 0, 1, 1);
+
+    // MarkerAttribute::.ctor, owned by the last TypeDef
+    let ctor = builder.method(0x1886, ".ctor", &[0x20, 0x00, 0x01]);
+
+    let a_of_var = builder.blob(&[0x15, 0x12, (a << 2) as u8, 0x01, 0x13, 0x00]); // A<!0>
+    builder.row(0x1b, &[a_of_var as u64]);
+    let b_of_int = builder.blob(&[0x15, 0x12, (b << 2) as u8, 0x01, 0x08]); // B<int32>
+    builder.row(0x1b, &[b_of_int as u64]);
+
+    builder.row(0x09, &[b as u64, coded(ifoo, 0, 2)]);
+    let value = builder.blob(&[0x01, 0x00, 0x00, 0x00]) as u64;
+    builder.row(0x0c, &[coded(c, 3, 5), coded(ctor, 2, 3), value]);
+    builder.build()
+}
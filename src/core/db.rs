@@ -153,40 +153,102 @@ pub(crate) trait CodedIndexEncode<T: TableKind>: CodedIndex {
 }
 
 pub(crate) struct Database<'db> {
+    data: &'db [u8],
+    file_header: &'db pe::image_file_header,
+    optional_magic: u16,
+    data_directories: &'db [pe::image_data_directory],
+    sections: &'db [pe::image_section_header],
+    cli: &'db pe::image_cor20_header,
     strings: &'db [u8],
     blobs: &'db [u8],
+    user_strings: &'db [u8],
     _guids: &'db [u8],
     tables: Tables<'db>,
     asm_name: Option<&'db str>,
 }
 
-pub fn is_database<P: AsRef<Path>>(path: P) -> io::Result<bool> {
-    let file = File::open(path.as_ref())?;
-    let mmap = unsafe { memmap::Mmap::map(&file)? };
+// The parts of the PE/COFF headers that are needed to find the CLI header (ECMA-335, II.25.2)
+struct PeHeaders<'a> {
+    file: &'a pe::image_file_header,
+    optional_magic: u16,
+    data_directories: &'a [pe::image_data_directory],
+    sections: &'a [pe::image_section_header],
+}
 
-    if mmap.len() < mem::size_of::<pe::image_dos_header>() {
-        return Ok(false);
+fn read_pe_headers(view: &[u8]) -> Result<PeHeaders<'_>> {
+    if view.len() < mem::size_of::<pe::image_dos_header>() {
+        return Err("Unexpected end of file".into());
     }
-    
-    let dos = unsafe { mmap.view_as::<pe::image_dos_header>(0) };
+
+    let dos = unsafe { view.view_as::<pe::image_dos_header>(0) };
 
     if dos.e_magic != 0x5A4D { // IMAGE_DOS_SIGNATURE
-        return Ok(false);
+        return Err("Invalid DOS signature".into());
     }
 
-    if mmap.len() < (dos.e_lfanew as usize + mem::size_of::<pe::image_nt_headers32>()) {
-        return Ok(false);
+    if dos.e_lfanew < 0 {
+        return Err("Invalid PE header offset".into());
+    }
+
+    // offsets are computed with checked arithmetic, so that malformed headers are reported as errors
+    let nt_offset = dos.e_lfanew as usize;
+    let optional_offset = match nt_offset.checked_add(4 + mem::size_of::<pe::image_file_header>()) {
+        Some(offset) if view.len() >= 2 && offset <= view.len() - 2 => offset,
+        _ => return Err("Unexpected end of file".into())
+    };
+
+    if *unsafe { view.view_as::<u32>(nt_offset) } != 0x00004550 { // IMAGE_NT_SIGNATURE
+        return Err("Invalid PE signature".into());
     }
 
-    let pe = unsafe { mmap.view_as::<pe::image_nt_headers32>(dos.e_lfanew as usize) };
+    let file = unsafe { view.view_as::<pe::image_file_header>(nt_offset + 4) };
 
-    if pe.FileHeader.NumberOfSections == 0 || pe.FileHeader.NumberOfSections > 100 {
-        return Ok(false);
+    if file.NumberOfSections == 0 || file.NumberOfSections > 100 {
+        return Err("Invalid PE section count".into());
     }
 
-    let com = &pe.OptionalHeader.DataDirectory[14]; // IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR
-    let sections = unsafe { mmap.view_as_slice::<pe::image_section_header>(dos.e_lfanew as usize + mem::size_of::<pe::image_nt_headers32>(),
-                                                                            pe.FileHeader.NumberOfSections as usize) };
+    // PE32 and PE32+ optional headers differ in size, so the data directories are at different offsets
+    let optional_magic = *unsafe { view.view_as::<u16>(optional_offset) };
+    let directories_offset = match optional_magic {
+        pe::IMAGE_NT_OPTIONAL_HDR32_MAGIC => 96, // NumberOfRvaAndSizes is the preceding field
+        pe::IMAGE_NT_OPTIONAL_HDR64_MAGIC => 112,
+        _ => return Err("Invalid PE optional header magic".into())
+    };
+    let optional_size = file.SizeOfOptionalHeader as usize;
+    let sections_offset = optional_offset.checked_add(optional_size).ok_or(crate::DecodeError("Unexpected end of file"))?;
+    let sections_size = file.NumberOfSections as usize * mem::size_of::<pe::image_section_header>();
+    match sections_offset.checked_add(sections_size) {
+        Some(end) if optional_size >= directories_offset && end <= view.len() => {},
+        _ => return Err("Unexpected end of file".into())
+    }
+
+    let directory_count = *unsafe { view.view_as::<u32>(optional_offset + directories_offset - 4) } as usize;
+    let directory_count = directory_count.min((optional_size - directories_offset) / mem::size_of::<pe::image_data_directory>());
+    let data_directories = unsafe { view.view_as_slice::<pe::image_data_directory>(optional_offset + directories_offset, directory_count) };
+    let sections = unsafe { view.view_as_slice::<pe::image_section_header>(sections_offset, file.NumberOfSections as usize) };
+
+    Ok(PeHeaders {
+        file,
+        optional_magic,
+        data_directories,
+        sections
+    })
+}
+
+pub fn is_database<P: AsRef<Path>>(path: P) -> io::Result<bool> {
+    let file = File::open(path.as_ref())?;
+    let mmap = unsafe { memmap::Mmap::map(&file)? };
+
+    let headers = match read_pe_headers(&mmap) {
+        Ok(headers) => headers,
+        Err(_) => return Ok(false)
+    };
+
+    let com = match headers.data_directories.get(pe::IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR) {
+        None => return Ok(false),
+        Some(d) => d
+    };
+    let sections = headers.sections;
     let section = match pe::section_from_rva(sections, com.VirtualAddress) {
         None => return Ok(false),
         Some(s) => s
@@ -194,6 +256,11 @@ pub fn is_database<P: AsRef<Path>>(path: P) -> io::Result<bool> {
 
     let offset = pe::offset_from_rva(section, com.VirtualAddress);
 
+    match offset.checked_add(mem::size_of::<pe::image_cor20_header>()) {
+        Some(end) if end <= mmap.len() => {},
+        _ => return Ok(false)
+    }
+
     let cli = unsafe { mmap.view_as::<pe::image_cor20_header>(offset) };
 
     if cli.cb as usize != mem::size_of::<pe::image_cor20_header>() {
@@ -207,7 +274,7 @@ pub fn is_database<P: AsRef<Path>>(path: P) -> io::Result<bool> {
 
     let offset = pe::offset_from_rva(section, cli.MetaData.VirtualAddress);
 
-    if *unsafe { mmap.view_as::<u32>(offset) } != 0x424a5342 {
+    if mmap.len() < 4 || offset > mmap.len() - 4 || *unsafe { mmap.view_as::<u32>(offset) } != 0x424a5342 {
         return Ok(false);
     }
 
@@ -240,34 +307,53 @@ fn read_string(strings: &[u8], index: u32) -> Result<&str> {
         std::str::from_utf8(&view[..len]).map_err(|_| "Invalid UTF8 in string heap".into())
     }
 
-impl<'db> Database<'db> {
-    pub fn load(data: &'db [u8]) -> Result<Database<'db>> {
+fn read_blob(blobs: &[u8], index: u32) -> Result<&[u8]> {
+    if index as usize >= blobs.len() {
+        return Err("Invalid blob index".into());
+    }
+    let view = &blobs[index as usize..];
+    let mut initial_byte: u8 = view[0];
+    let blob_size_bytes: usize = match initial_byte >> 5 {
+        0 | 1 | 2 | 3 => {
+            initial_byte &= 0x7f;
+            1
+        },
+        4 | 5 => {
+            initial_byte &= 0x3f;
+            2
+        },
+        6 => {
+            initial_byte &= 0x1f;
+            4
+        },
+        _ => return Err("Invalid blob encoding".into())
+    };
 
-        let view = data;
+    let mut blob_size = initial_byte as usize;
 
-        if view.len() < mem::size_of::<pe::image_dos_header>() {
-            return Err("Unexpected end of file".into());
-        }
-        
-        let dos = unsafe { view.view_as::<pe::image_dos_header>(0) };
+    for &byte in view.sub(1, blob_size_bytes - 1) {
+        blob_size = (blob_size << 8) + byte as usize;
+    }
 
-        if dos.e_magic != 0x5A4D { // IMAGE_DOS_SIGNATURE
-            return Err("Invalid DOS signature".into());
-        }
+    if blob_size_bytes + blob_size > view.len() {
+        return Err("Blob exceeds heap".into());
+    }
 
-        if view.len() < (dos.e_lfanew as usize + mem::size_of::<pe::image_nt_headers32>()) {
-            return Err("Unexpected end of file".into());
-        }
+    Ok(view.sub(blob_size_bytes, blob_size))
+}
 
-        let pe = unsafe { view.view_as::<pe::image_nt_headers32>(dos.e_lfanew as usize) };
+impl<'db> Database<'db> {
+    pub fn load(data: &'db [u8]) -> Result<Database<'db>> {
 
-        if pe.FileHeader.NumberOfSections == 0 || pe.FileHeader.NumberOfSections > 100 {
-            return Err("Invalid PE section count".into());
-        }
+        let view = data;
+
+        let headers = read_pe_headers(view)?;
 
-        let com = &pe.OptionalHeader.DataDirectory[14]; // IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR
-        let sections = unsafe { view.view_as_slice::<pe::image_section_header>(dos.e_lfanew as usize + mem::size_of::<pe::image_nt_headers32>(),
-                                                                               pe.FileHeader.NumberOfSections as usize) };
+        let com = match headers.data_directories.get(pe::IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR) {
+            None => return Err("PE image has no CLI header data directory".into()),
+            Some(d) => d
+        };
+        let sections = headers.sections;
         let section = match pe::section_from_rva(sections, com.VirtualAddress) {
             None => return Err("PE section containing CLI header not found".into()),
             Some(s) => s
@@ -275,6 +361,11 @@ impl<'db> Database<'db> {
 
         let offset = pe::offset_from_rva(section, com.VirtualAddress);
 
+        match offset.checked_add(mem::size_of::<pe::image_cor20_header>()) {
+            Some(end) if end <= view.len() => {},
+            _ => return Err("Unexpected end of file".into())
+        }
+
         let cli = unsafe { view.view_as::<pe::image_cor20_header>(offset) };
 
         if cli.cb as usize != mem::size_of::<pe::image_cor20_header>() {
@@ -300,6 +391,7 @@ impl<'db> Database<'db> {
         let mut strings: Option<_> = None;
         let mut blobs: Option<_> = None;
         let mut guids: Option<_> = None;
+        let mut user_strings: Option<_> = None;
 
         for _ in 0..stream_count {
             let stream = unsafe { remaining.view_as::<stream_range>(0) };
@@ -315,14 +407,13 @@ impl<'db> Database<'db> {
                 b"#GUID" => {
                     guids = Some(view.sub(offset + stream.offset as usize, stream.size as usize))
                 },
+                b"#US" => {
+                    user_strings = Some(view.sub(offset + stream.offset as usize, stream.size as usize))
+                },
                 b"#~" => {
                     tables = Some(view.sub(offset + stream.offset as usize, stream.size as usize))
                 },
-                _ => {
-                    if name != b"#US" {
-                        return Err("Unknown metadata stream".into());
-                    }
-                }
+                _ => return Err("Unknown metadata stream".into())
             }
 
             remaining = &remaining[stream_offset(name)..];
@@ -344,6 +435,7 @@ impl<'db> Database<'db> {
             Some(v) => v,
             None => return Err("Missing tables stream".into())
         };
+        let user_strings = user_strings.unwrap_or(&[]); // only needed for method bodies
 
         let heap_sizes = *unsafe { tables.view_as::<u8>(6) };
         let string_index_size = if heap_sizes >> 0 & 1 == 1 { DynamicSize::Size4 } else { DynamicSize::Size2 };
@@ -496,8 +588,15 @@ impl<'db> Database<'db> {
             };
 
         Ok(Database {
+            data,
+            file_header: headers.file,
+            optional_magic: headers.optional_magic,
+            data_directories: headers.data_directories,
+            sections,
+            cli,
             strings: strings,
             blobs: blobs,
+            user_strings,
             _guids: guids,
             tables: t,
             asm_name: asm_name
@@ -521,32 +620,58 @@ impl<'db> Database<'db> {
         read_string(self.strings, index)
     }
 
-    pub(crate) fn get_blob(&self, index: u32) -> Result<&[u8]> {
-        let view = &self.blobs[index as usize..];
-        let mut initial_byte: u8 = view[0];
-        let blob_size_bytes: usize = match initial_byte >> 5 {
-            0 | 1 | 2 | 3 => {
-                initial_byte &= 0x7f;
-                1
-            },
-            4 | 5 => {
-                initial_byte &= 0x3f;
-                2
-            },
-            6 => {
-                initial_byte &= 0x1f;
-                4
-            },
-            _ => return Err("Invalid blob encoding".into())
+    /// Returns the file contents starting at the given RVA, up to the end of
+    /// the raw data of the containing section.
+    pub(crate) fn get_rva_data(&self, rva: u32) -> Result<&'db [u8]> {
+        let section = match pe::section_from_rva(self.sections, rva) {
+            None => return Err("PE section containing RVA not found".into()),
+            Some(s) => s
         };
 
-        let mut blob_size = initial_byte as usize;
+        let offset = pe::offset_from_rva(section, rva);
+        let end = section.PointerToRawData as usize + section.SizeOfRawData as usize;
+        if offset >= end || end > self.data.len() {
+            return Err("RVA points outside of the file".into());
+        }
+
+        Ok(&self.data[offset..end])
+    }
 
-        for &byte in view.sub(1, blob_size_bytes - 1) {
-            blob_size = (blob_size << 8) + byte as usize;
+    /// Returns `len` bytes of the file contents starting at the given RVA. The RVA must lie
+    /// within the raw data of a section even if `len` is 0, so the end of a section is not readable.
+    pub(crate) fn read_rva(&self, rva: u32, len: u32) -> Result<&'db [u8]> {
+        let data = self.get_rva_data(rva)?;
+        if data.len() < len as usize {
+            return Err("RVA range exceeds the raw data of its section".into());
         }
+        Ok(&data[..len as usize])
+    }
+
+    pub(crate) fn cli_header(&self) -> crate::image::CliHeader {
+        crate::image::CliHeader::from_raw(self.cli)
+    }
+
+    pub(crate) fn pe_header(&self) -> crate::image::PeHeader {
+        crate::image::PeHeader::from_raw(self.file_header, self.optional_magic, self.data_directories)
+    }
 
-        Ok(view.sub(blob_size_bytes, blob_size))
+    pub(crate) fn sections(&self) -> &'db [pe::image_section_header] {
+        self.sections
+    }
+
+    pub(crate) fn get_blob(&self, index: u32) -> Result<&[u8]> {
+        read_blob(self.blobs, index)
+    }
+
+    // ECMA-335, II.24.2.4
+    pub(crate) fn get_user_string(&self, index: u32) -> Result<String> {
+        let bytes = read_blob(self.user_strings, index)?;
+        // the final byte is a flag that tells whether any character needs special handling
+        let units: Vec<u16> = bytes[..bytes.len().saturating_sub(1)].chunks_exact(2)
+                                   .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                                   .collect();
+        // user strings are not guaranteed to be well-formed UTF-16, e.g. they may contain lone surrogates
+        Ok(String::from_utf16_lossy(&units))
     }
 }
 
@@ -567,3 +692,59 @@ impl<'db> crate::AssemblyAccess for Database<'db> {
         self.asm_name
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Database;
+    use crate::core::builder::ImageBuilder;
+
+    #[test]
+    fn test_malformed_pe_headers() {
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        let image = builder.build();
+        assert!(Database::from_data(&image).is_ok());
+
+        // e_lfanew is negative, points past the end of the file, or makes offsets overflow
+        for &e_lfanew in &[-1i32, 0x0fff_ffff, i32::MAX] {
+            let mut image = image.clone();
+            image[0x3c..0x40].copy_from_slice(&e_lfanew.to_le_bytes());
+            assert!(Database::from_data(&image).is_err());
+        }
+
+        // SizeOfOptionalHeader points the section headers past the end of the file
+        let mut image = image.clone();
+        image[0x94..0x96].copy_from_slice(&0xffffu16.to_le_bytes());
+        assert!(Database::from_data(&image).is_err());
+        let path = std::env::temp_dir().join(format!("climeta-malformed-{}.winmd", std::process::id()));
+        std::fs::write(&path, &image).unwrap();
+        let is_database = Database::is_database(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(!is_database.unwrap());
+    }
+
+    #[test]
+    fn test_read_rva() {
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        let rva = builder.data(&[1, 2, 3]);
+        let image = builder.build();
+        let db = Database::from_data(&image).unwrap();
+        assert_eq!(db.read_rva(rva, 3).unwrap(), [1, 2, 3]);
+        assert!(db.read_rva(rva, 0).unwrap().is_empty());
+
+        let section = db.sections().next().unwrap();
+        let end = section.virtual_address() + section.size_of_raw_data();
+        assert_eq!(db.read_rva(end - 1, 1).unwrap().len(), 1);
+        assert!(db.read_rva(end - 1, 2).is_err());
+        // the end of a section is not within it, even for an empty read
+        assert!(db.read_rva(end, 0).is_err());
+        assert!(db.read_rva(u32::MAX, 0).is_err());
+
+        // the CLI header directory points at the last byte of the file
+        let mut image = image.clone();
+        let directory = 0x98 + 96 + 14 * 8;
+        image[directory..directory + 4].copy_from_slice(&(end - 1).to_le_bytes());
+        assert!(Database::from_data(&image).is_err());
+    }
+}
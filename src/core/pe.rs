@@ -1,5 +1,9 @@
 #![allow(non_snake_case)]
 
+pub const IMAGE_NT_OPTIONAL_HDR32_MAGIC: u16 = 0x10b;
+pub const IMAGE_NT_OPTIONAL_HDR64_MAGIC: u16 = 0x20b;
+pub const IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR: usize = 14;
+
 #[repr(C)]
 pub struct image_dos_header
 {
@@ -43,50 +47,6 @@ pub struct image_data_directory
     pub Size: u32,
 }
 
-#[repr(C)]
-pub struct image_optional_header32
-{
-    pub Magic: u16,
-    pub MajorLinkerVersion: u8,
-    pub MinorLinkerVersion: u8,
-    pub SizeOfCode: u32,
-    pub SizeOfInitializedData: u32,
-    pub SizeOfUninitializedData: u32,
-    pub AddressOfEntryPoint: u32,
-    pub BaseOfCode: u32,
-    pub BaseOfData: u32,
-    pub ImageBase: u32,
-    pub SectionAlignment: u32,
-    pub FileAlignment: u32,
-    pub MajorOperatingSystemVersion: u16,
-    pub MinorOperatingSystemVersion: u16,
-    pub MajorImageVersion: u16,
-    pub MinorImageVersion: u16,
-    pub MajorSubsystemVersion: u16,
-    pub MinorSubsystemVersion: u16,
-    pub Win32VersionValue: u32,
-    pub SizeOfImage: u32,
-    pub SizeOfHeaders: u32,
-    pub CheckSum: u32,
-    pub Subsystem: u16,
-    pub DllCharacteristics: u16,
-    pub SizeOfStackReserve: u32,
-    pub SizeOfStackCommit: u32,
-    pub SizeOfHeapReserve: u32,
-    pub SizeOfHeapCommit: u32,
-    pub LoaderFlags: u32,
-    pub NumberOfRvaAndSizes: u32,
-    pub DataDirectory: [image_data_directory; 16],
-}
-
-#[repr(C)]
-pub struct image_nt_headers32
-{
-    pub Signature: u32,
-    pub FileHeader: image_file_header,
-    pub OptionalHeader: image_optional_header32,
-}
-
 #[repr(C)]
 pub struct image_section_header {
     pub Name: [u8; 8], // IMAGE_SIZEOF_SHORT_NAME
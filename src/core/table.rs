@@ -46,7 +46,7 @@ impl<'db, T> TableInfo<'db, T> {
     {
         let data_size = self.m_columns[Col::idx()].size;
 
-        if row > self.len() {
+        if row >= self.len() {
             return Err("Invalid row index".into());
         }
         let input = &self.m_data.unwrap()[row as usize * self.m_row_size as usize +
@@ -96,7 +96,7 @@ impl<'db, T: TableKind> Table<'db, T> where &'db T: TableRowAccess<Table=Self> {
     }
 
     pub fn get_row(&self, row: u32) -> Result<<&'db T as TableRowAccess>::Out> {
-        if row > self.len() {
+        if row >= self.len() {
             return Err("Invalid row index".into());
         }
         
@@ -125,12 +125,57 @@ pub(crate) struct Row<'db, T: TableKind> {
     m_row: u32,
 }
 
+// A row is identified by the database it belongs to (compared by address,
+// because each database is loaded only once) and its index within the table.
+// The table itself is already determined by `T`.
+impl<'db, T: TableKind> Row<'db, T> {
+    fn identity(&self) -> (usize, u32) {
+        (self.m_table.db as *const Database<'db> as usize, self.m_row)
+    }
+}
+
+impl<'db, T: TableKind> PartialEq for Row<'db, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl<'db, T: TableKind> Eq for Row<'db, T> {}
+
+impl<'db, T: TableKind> std::hash::Hash for Row<'db, T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.identity().hash(state)
+    }
+}
+
+impl<'db, T: TableKind> PartialOrd for Row<'db, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'db, T: TableKind> Ord for Row<'db, T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.identity().cmp(&other.identity())
+    }
+}
+
 pub struct TableRowIterator<'db, T: TableKind> {
     m_table: Table<'db, T>,
     m_row: u32, // the next row to yield
     m_end: u32, // end of this iterator's range (exclusive)
 }
 
+impl<'db, T: TableKind> TableRowIterator<'db, T> {
+    pub(crate) fn empty(table: Table<'db, T>) -> TableRowIterator<'db, T> {
+        TableRowIterator {
+            m_table: table,
+            m_row: 0,
+            m_end: 0
+        }
+    }
+}
+
 impl<'db, T: TableKind> Iterator for TableRowIterator<'db, T>
     where &'db T: TableRowAccess<Table=Table<'db, T>>
 {
@@ -255,6 +300,25 @@ impl<'db, T: TableKind> Row<'db, T> where &'db T: TableRowAccess<Table=Table<'db
         target_table.get_row(row - 1)
     }
 
+    // Finds the row of the `Owner` table whose list (stored in column `Col`) contains this row,
+    // e.g. the TypeDef that owns a MethodDef (this is the inverse of `get_list`)
+    pub(crate) fn get_list_owner<Col: ColumnIndex, Owner>(&self) -> Result<<&'db Owner as TableRowAccess>::Out>
+        where db::Database<'db>: db::TableInfoAccess<'db, Owner>,
+              Owner: TableKind + ColumnAccess<Col>, u32: ReadValue<Owner::ColumnSize>,
+              &'db Owner: TableRowAccess<Table=Table<'db, Owner>>,
+              <&'db Owner as TableRowAccess>::Out: TableRow<Kind=Owner>
+    {
+        let owner_table = self.m_table.db.get_table::<<&'db Owner as TableRowAccess>::Out>();
+        // list columns store 1-based indices, find the first owner whose list starts after this row
+        let next = crate::core::lower_bound_with(0, owner_table.len() as usize, |i| {
+                        owner_table.get_value::<Col, u32>(i as u32).unwrap()
+                    }, self.m_row + 2);
+        if next == 0 {
+            return Err("Row is not contained in any list".into());
+        }
+        owner_table.get_row(next as u32 - 1)
+    }
+
     pub(crate) fn get_list_by_key<Target: TableDescWithKey>(&self, encoded_idx: u32) -> Result<TableRowIterator<'db, Target>>
         where db::Database<'db>: db::TableInfoAccess<'db, Target>,
               Target::Columns: crate::core::columns::ColumnTupleAccess<Target::KeyColumn>,
@@ -294,3 +358,73 @@ impl<'db, T: TableKind> Row<'db, T> where &'db T: TableRowAccess<Table=Table<'db
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TableInfo;
+    use crate::core::columns::{Col0, FixedSize4};
+    use crate::schema::marker;
+
+    #[test]
+    fn test_row_bounds() {
+        let data = [1, 0, 0, 0, 2, 0, 0, 0];
+        let mut table = TableInfo::<marker::AssemblyProcessor>::default();
+        table.set_columns((FixedSize4,));
+        table.set_row_count(2);
+        table.set_data(&data);
+        assert_eq!(table.get_value::<Col0, u32>(1).unwrap(), 2);
+        // the row count is one past the last row
+        assert!(table.get_value::<Col0, u32>(2).is_err());
+    }
+
+    #[test]
+    fn test_get_row_bounds() {
+        use crate::core::builder::{ImageBuilder, CLASS};
+        use crate::{Database, schema};
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        builder.type_def(CLASS, "N", "Last", 0, 1, 1);
+        let image = builder.build();
+        let db = Database::from_data(&image).unwrap();
+        let types = db.table::<schema::TypeDef>();
+        assert_eq!(types.get_row(1).unwrap().type_name().unwrap(), "Last");
+        assert!(types.get_row(2).is_err());
+    }
+
+    #[test]
+    fn test_row_identity() {
+        use std::collections::{BTreeMap, HashSet};
+        use crate::core::builder::{ImageBuilder, coded, CLASS};
+        use crate::{Database, schema};
+        use crate::schema::TypeDefOrRef;
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        builder.type_def(CLASS, "N", "A", 0, 1, 1);
+        builder.type_def(CLASS, "N", "B", coded(2, 0, 2), 1, 1);
+        let image = builder.build();
+        let first = Database::from_data(&image).unwrap();
+        let second = Database::from_data(&image).unwrap();
+
+        let types: HashSet<_> = first.table::<schema::TypeDef>().iter().chain(first.table::<schema::TypeDef>().iter()).collect();
+        assert_eq!(types.len(), 3);
+
+        let a = first.table::<schema::TypeDef>().get_row(1).unwrap();
+        let b = first.table::<schema::TypeDef>().get_row(2).unwrap();
+        assert!(a < b);
+        let base = b.extends().unwrap().unwrap();
+        assert!(base == TypeDefOrRef::TypeDef(a.clone()));
+
+        // the same row of another database is a different row
+        let other_a = second.table::<schema::TypeDef>().get_row(1).unwrap();
+        assert_ne!(a, other_a);
+        assert!(!types.contains(&other_a));
+
+        let mut derived = BTreeMap::new();
+        derived.insert(base, "B");
+        derived.insert(TypeDefOrRef::TypeDef(a), "B");
+        derived.insert(TypeDefOrRef::TypeDef(other_a), "none");
+        assert_eq!(derived.len(), 2);
+    }
+}
@@ -0,0 +1,340 @@
+//! Information about the PE/COFF image that contains the metadata.
+
+use crate::core::pe;
+use crate::core::BitView;
+
+// ECMA-335, II.25.2.3.3
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DataDirectory {
+    pub virtual_address: u32,
+    pub size: u32,
+}
+
+impl DataDirectory {
+    pub(crate) fn from_raw(raw: &pe::image_data_directory) -> DataDirectory {
+        DataDirectory {
+            virtual_address: raw.VirtualAddress,
+            size: raw.Size
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.virtual_address == 0 || self.size == 0
+    }
+}
+
+// ECMA-335, II.25.3.3.1
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CliFlags(pub(crate) u32);
+
+#[allow(non_upper_case_globals)]
+mod bits {
+    pub const ILOnly_bit: usize = 0;
+    pub const Required32Bit_bit: usize = 1;
+    pub const ILLibrary_bit: usize = 2;
+    pub const StrongNameSigned_bit: usize = 3;
+    pub const NativeEntryPoint_bit: usize = 4;
+    pub const TrackDebugData_bit: usize = 16;
+    pub const Preferred32Bit_bit: usize = 17;
+}
+
+impl CliFlags {
+    pub fn il_only(&self) -> bool {
+        self.0.get_bit(bits::ILOnly_bit)
+    }
+
+    pub fn required_32bit(&self) -> bool {
+        self.0.get_bit(bits::Required32Bit_bit)
+    }
+
+    pub fn il_library(&self) -> bool {
+        self.0.get_bit(bits::ILLibrary_bit)
+    }
+
+    pub fn strong_name_signed(&self) -> bool {
+        self.0.get_bit(bits::StrongNameSigned_bit)
+    }
+
+    pub fn native_entry_point(&self) -> bool {
+        self.0.get_bit(bits::NativeEntryPoint_bit)
+    }
+
+    pub fn track_debug_data(&self) -> bool {
+        self.0.get_bit(bits::TrackDebugData_bit)
+    }
+
+    pub fn preferred_32bit(&self) -> bool {
+        self.0.get_bit(bits::Preferred32Bit_bit)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EntryPoint {
+    /// Metadata token of a MethodDef, or of a File for multi-module assemblies.
+    Token(u32),
+    /// RVA of a native entry point (only if `CliFlags::native_entry_point()` is set).
+    NativeRva(u32),
+}
+
+/// The CLI header (also known as COR20 header) of an image (ECMA-335, II.25.3.3).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CliHeader {
+    pub major_runtime_version: u16,
+    pub minor_runtime_version: u16,
+    pub metadata: DataDirectory,
+    pub flags: CliFlags,
+    pub entry_point: Option<EntryPoint>,
+    pub resources: DataDirectory,
+    pub strong_name_signature: DataDirectory,
+    pub code_manager_table: DataDirectory,
+    pub vtable_fixups: DataDirectory,
+    pub export_address_table_jumps: DataDirectory,
+    pub managed_native_header: DataDirectory,
+}
+
+impl CliHeader {
+    pub(crate) fn from_raw(raw: &pe::image_cor20_header) -> CliHeader {
+        let flags = CliFlags(raw.Flags);
+        let entry_point = match raw.Union_EntryPointToken_EntryPointRVA {
+            0 => None,
+            value if flags.native_entry_point() => Some(EntryPoint::NativeRva(value)),
+            value => Some(EntryPoint::Token(value))
+        };
+        CliHeader {
+            major_runtime_version: raw.MajorRuntimeVersion,
+            minor_runtime_version: raw.MinorRuntimeVersion,
+            metadata: DataDirectory::from_raw(&raw.MetaData),
+            flags,
+            entry_point,
+            resources: DataDirectory::from_raw(&raw.Resources),
+            strong_name_signature: DataDirectory::from_raw(&raw.StrongNameSignature),
+            code_manager_table: DataDirectory::from_raw(&raw.CodeManagerTable),
+            vtable_fixups: DataDirectory::from_raw(&raw.VTableFixups),
+            export_address_table_jumps: DataDirectory::from_raw(&raw.ExportAddressTableJumps),
+            managed_native_header: DataDirectory::from_raw(&raw.ManagedNativeHeader)
+        }
+    }
+}
+
+// PE/COFF, "Machine Types"
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Machine {
+    Unknown,
+    I386,
+    Amd64,
+    Arm,
+    ArmNT,
+    Arm64,
+    IA64,
+    Other(u16),
+}
+
+impl Machine {
+    fn from_u16(value: u16) -> Machine {
+        match value {
+            0x0000 => Machine::Unknown,
+            0x014c => Machine::I386,
+            0x8664 => Machine::Amd64,
+            0x01c0 => Machine::Arm,
+            0x01c4 => Machine::ArmNT,
+            0xaa64 => Machine::Arm64,
+            0x0200 => Machine::IA64,
+            other => Machine::Other(other)
+        }
+    }
+}
+
+// PE/COFF, "Characteristics"
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FileCharacteristics(pub(crate) u16);
+
+#[allow(non_upper_case_globals)]
+mod file_bits {
+    pub const RelocsStripped_bit: usize = 0;
+    pub const ExecutableImage_bit: usize = 1;
+    pub const LargeAddressAware_bit: usize = 5;
+    pub const Machine32Bit_bit: usize = 8;
+    pub const Dll_bit: usize = 13;
+}
+
+impl FileCharacteristics {
+    pub fn relocs_stripped(&self) -> bool {
+        self.0.get_bit(file_bits::RelocsStripped_bit)
+    }
+
+    pub fn executable_image(&self) -> bool {
+        self.0.get_bit(file_bits::ExecutableImage_bit)
+    }
+
+    pub fn large_address_aware(&self) -> bool {
+        self.0.get_bit(file_bits::LargeAddressAware_bit)
+    }
+
+    pub fn machine_32bit(&self) -> bool {
+        self.0.get_bit(file_bits::Machine32Bit_bit)
+    }
+
+    pub fn dll(&self) -> bool {
+        self.0.get_bit(file_bits::Dll_bit)
+    }
+}
+
+// PE/COFF, "Optional Header Data Directories"
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DirectoryEntry {
+    Export = 0,
+    Import = 1,
+    Resource = 2,
+    Exception = 3,
+    Certificate = 4,
+    BaseRelocation = 5,
+    Debug = 6,
+    Architecture = 7,
+    GlobalPtr = 8,
+    Tls = 9,
+    LoadConfig = 10,
+    BoundImport = 11,
+    ImportAddressTable = 12,
+    DelayImport = 13,
+    CliHeader = 14,
+}
+
+/// The COFF file header and the data directories of the optional header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeHeader {
+    pub machine: Machine,
+    /// Seconds since 1970-01-01 (or a hash of the image for deterministic builds).
+    pub timestamp: u32,
+    pub characteristics: FileCharacteristics,
+    /// Whether the optional header has the PE32+ (64-bit) format.
+    pub pe32_plus: bool,
+    pub data_directories: Vec<DataDirectory>,
+}
+
+impl PeHeader {
+    pub(crate) fn from_raw(file: &pe::image_file_header, optional_magic: u16, directories: &[pe::image_data_directory]) -> PeHeader {
+        PeHeader {
+            machine: Machine::from_u16(file.Machine),
+            timestamp: file.TimeDateStamp,
+            characteristics: FileCharacteristics(file.Characteristics),
+            pe32_plus: optional_magic == pe::IMAGE_NT_OPTIONAL_HDR64_MAGIC,
+            data_directories: directories.iter().map(DataDirectory::from_raw).collect()
+        }
+    }
+
+    /// Returns the given data directory, which is empty if the image does not contain it.
+    pub fn data_directory(&self, entry: DirectoryEntry) -> DataDirectory {
+        self.data_directories.get(entry as usize).cloned().unwrap_or_default()
+    }
+}
+
+/// A section header of the image.
+#[derive(Copy, Clone)]
+pub struct Section<'db>(pub(crate) &'db pe::image_section_header);
+
+impl<'db> Section<'db> {
+    /// The section name (e.g. `.text`), without padding.
+    pub fn name(&self) -> &'db [u8] {
+        let name = &self.0.Name;
+        match name.iter().position(|b| *b == 0) {
+            Some(len) => &name[..len],
+            None => &name[..]
+        }
+    }
+
+    pub fn virtual_address(&self) -> u32 {
+        self.0.VirtualAddress
+    }
+
+    pub fn virtual_size(&self) -> u32 {
+        self.0.Union_PhysicalAddress_VirtualSize
+    }
+
+    pub fn pointer_to_raw_data(&self) -> u32 {
+        self.0.PointerToRawData
+    }
+
+    pub fn size_of_raw_data(&self) -> u32 {
+        self.0.SizeOfRawData
+    }
+
+    pub fn characteristics(&self) -> u32 {
+        self.0.Characteristics
+    }
+
+    pub fn contains_rva(&self, rva: u32) -> bool {
+        pe::section_from_rva(std::slice::from_ref(self.0), rva).is_some()
+    }
+}
+
+impl<'db> std::fmt::Debug for Section<'db> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Section({:?})", String::from_utf8_lossy(self.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CliHeader, EntryPoint};
+    use crate::core::pe::{image_cor20_header, image_data_directory};
+
+    fn dir(virtual_address: u32, size: u32) -> image_data_directory {
+        image_data_directory { VirtualAddress: virtual_address, Size: size }
+    }
+
+    fn raw_header(flags: u32, entry_point: u32) -> image_cor20_header {
+        image_cor20_header {
+            cb: 72,
+            MajorRuntimeVersion: 2,
+            MinorRuntimeVersion: 5,
+            MetaData: dir(0x2070, 0x400),
+            Flags: flags,
+            Union_EntryPointToken_EntryPointRVA: entry_point,
+            Resources: dir(0, 0),
+            StrongNameSignature: dir(0x2500, 0x80),
+            CodeManagerTable: dir(0, 0),
+            VTableFixups: dir(0, 0),
+            ExportAddressTableJumps: dir(0, 0),
+            ManagedNativeHeader: dir(0, 0)
+        }
+    }
+
+    #[test]
+    fn test_cli_header() {
+        let header = CliHeader::from_raw(&raw_header(0x0000_0009, 0x0600_0001));
+        assert!(header.flags.il_only());
+        assert!(header.flags.strong_name_signed());
+        assert!(!header.flags.required_32bit());
+        assert_eq!(header.entry_point, Some(EntryPoint::Token(0x0600_0001)));
+        assert_eq!(header.strong_name_signature.size, 0x80);
+        assert!(header.resources.is_empty());
+
+        let header = CliHeader::from_raw(&raw_header(0x0000_0010, 0x1234));
+        assert_eq!(header.entry_point, Some(EntryPoint::NativeRva(0x1234)));
+        let header = CliHeader::from_raw(&raw_header(0x0000_0001, 0));
+        assert_eq!(header.entry_point, None);
+    }
+
+    #[test]
+    fn test_section() {
+        use crate::core::pe::image_section_header;
+        let raw = image_section_header {
+            Name: *b".text\0\0\0",
+            Union_PhysicalAddress_VirtualSize: 0x100,
+            VirtualAddress: 0x2000,
+            SizeOfRawData: 0x200,
+            PointerToRawData: 0x400,
+            PointerToRelocations: 0,
+            PointerToLinenumbers: 0,
+            NumberOfRelocations: 0,
+            NumberOfLinenumbers: 0,
+            Characteristics: 0x6000_0020
+        };
+        let section = super::Section(&raw);
+        assert_eq!(section.name(), b".text");
+        assert!(section.contains_rva(0x20ff));
+        assert!(!section.contains_rva(0x2100));
+        assert_eq!(super::Machine::from_u16(0x8664), super::Machine::Amd64);
+        assert_eq!(super::Machine::from_u16(0x1234), super::Machine::Other(0x1234));
+    }
+}
@@ -1,11 +1,13 @@
 #[macro_use] extern crate num_derive;
+// allows code generated by climeta_derive (which uses `::climeta` paths) to be used within this crate
+extern crate self as climeta;
 use memmap::Mmap;
 use stable_deref_trait::StableDeref;
 use owning_ref::OwningHandle;
 use elsa::FrozenVec;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::error::Error;
 use std::io;
 use std::fmt;
@@ -18,6 +20,7 @@ mod core;
 use crate::core::db;
 
 pub mod schema;
+pub mod image;
 
 #[derive(Clone, Debug)]
 pub struct DecodeError(&'static str);
@@ -190,7 +193,7 @@ impl<'db> Database<'db> {
         let file = File::open(path.as_ref())?;
         let mmap = StableMmap(unsafe { Mmap::map(&file)? });
         Ok(Database(DatabaseInner::Owned(
-            OwningHandle::try_new(mmap, |ptr: *const [u8]| db::Database::load(unsafe { &(*ptr)[..] }).map(|db| DerefDatabase(db)))?
+            OwningHandle::try_new(mmap, |ptr: *const [u8]| db::Database::load(unsafe { &*ptr }).map(|db| DerefDatabase(db)))?
         )))
     }
 
@@ -207,6 +210,49 @@ impl<'db> Database<'db> {
     pub fn is_database<P: AsRef<Path>>(path: P) -> io::Result<bool> {
         db::is_database(path)
     }
+
+    pub fn cli_header(&self) -> image::CliHeader {
+        self.0.cli_header()
+    }
+
+    pub fn pe_header(&self) -> image::PeHeader {
+        self.0.pe_header()
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item=image::Section<'_>> + '_ {
+        self.0.sections().iter().map(image::Section)
+    }
+
+    /// Reads `len` bytes of the image starting at the given RVA (relative virtual address).
+    /// Fails if the RVA is not within the raw data of a section, even when `len` is 0.
+    pub fn read_rva(&self, rva: u32, len: u32) -> Result<&[u8]> {
+        self.0.read_rva(rva, len)
+    }
+
+    /// Returns the managed entry point method, if there is one.
+    pub fn entry_point(&'db self) -> Result<Option<schema::MethodDef<'db>>> {
+        match self.cli_header().entry_point {
+            Some(image::EntryPoint::Token(token)) if token >> 24 == 0x06 => { // MethodDef table
+                let row = token & 0x00ff_ffff;
+                if row == 0 {
+                    return Err("Invalid entry point token".into());
+                }
+                Ok(Some(self.table::<schema::MethodDef>().get_row(row - 1)?))
+            },
+            _ => Ok(None)
+        }
+    }
+
+    /// Returns the strong name signature, or `None` if the image is not strong-name signed,
+    /// i.e. if the `StrongNameSigned` CLI flag is clear or the signature directory is empty.
+    pub fn strong_name_signature(&self) -> Result<Option<&[u8]>> {
+        let header = self.cli_header();
+        let dir = header.strong_name_signature;
+        if !header.flags.strong_name_signed() || dir.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(self.read_rva(dir.virtual_address, dir.size)?))
+    }
 }
 
 pub trait AssemblyAccess {
@@ -239,14 +285,16 @@ pub trait TableRowAccess {
 #[derive(Default)]
 pub struct Cache<'db> {
     databases: FrozenVec<Box<Database<'db>>>,
-    namespace_map: RefCell<HashMap<&'db str, MemberCache<'db>>>
+    namespace_map: RefCell<HashMap<&'db str, MemberCache<'db>>>,
+    reverse_index: RefCell<Option<ReverseIndex<'db>>>
 }
 
 impl<'db> Cache<'db> {
     pub fn new() -> Cache<'db> {
         Cache {
             databases: FrozenVec::new(),
-            namespace_map: RefCell::new(HashMap::new())
+            namespace_map: RefCell::new(HashMap::new()),
+            reverse_index: RefCell::new(None)
         }
     }
 
@@ -255,6 +303,7 @@ impl<'db> Cache<'db> {
 
         let idx = self.databases.len();
         self.databases.push(Box::new(database));
+        *self.reverse_index.borrow_mut() = None; // will be rebuilt on next use
         let db = &self.databases[idx];
         for typ in db.table::<schema::TypeDef>() {
             // if !type.flags().windows_runtime() {
@@ -276,9 +325,118 @@ impl<'db> Cache<'db> {
         map.get(type_namespace).and_then(|ns| ns.types.get(type_name).map(|t| t.clone()))
     }
 
+    /// Looks up a member of the type with the given full name (e.g. `"Windows.Foundation.Uri"`).
+    /// Fields are searched first, then methods, properties and events. For overloaded methods,
+    /// the first overload is returned (see `find_member_with_signature`).
+    pub fn find_member(&self, type_name: &str, member_name: &str) -> Result<Option<schema::Member<'db>>> {
+        use schema::Member;
+
+        let typ = match self.find_full_name(type_name) {
+            Some(typ) => typ,
+            None => return Ok(None)
+        };
+        if let Some(field) = typ.find_field(member_name)? {
+            return Ok(Some(Member::Field(field)));
+        }
+        if let Some(method) = typ.find_method(member_name)? {
+            return Ok(Some(Member::Method(method)));
+        }
+        if let Some(property) = typ.find_property(member_name)? {
+            return Ok(Some(Member::Property(property)));
+        }
+        Ok(typ.find_event(member_name)?.map(Member::Event))
+    }
+
+    /// Looks up the overload of a method whose signature matches `signature`, e.g.
+    /// to find the MethodDef that is referenced by a MemberRef in another database.
+    pub fn find_member_with_signature(&self, type_name: &str, member_name: &str, signature: &schema::MethodDefSig<'_>) -> Result<Option<schema::MethodDef<'db>>> {
+        match self.find_full_name(type_name) {
+            Some(typ) => typ.find_method_with_signature(member_name, signature),
+            None => Ok(None)
+        }
+    }
+
+    fn find_full_name(&self, full_name: &str) -> Option<schema::TypeDef<'db>> {
+        match full_name.rfind('.') {
+            None => self.find("", full_name),
+            Some(dot) => self.find(&full_name[..dot], &full_name[dot+1 ..])
+        }
+    }
+
     pub fn iter(&'db self) -> impl Iterator<Item = &'db Database<'db>> {
         self.into_iter()
     }
+
+    /// Returns the names of all namespaces that contain at least one type, in sorted order.
+    pub fn namespaces(&self) -> impl Iterator<Item = &'db str> {
+        let map = self.namespace_map.borrow();
+        let mut namespaces: Vec<_> = map.keys().cloned().collect();
+        namespaces.sort();
+        namespaces.into_iter()
+    }
+
+    /// Returns all types in the given namespace (excluding nested namespaces), sorted by name.
+    pub fn types_in(&self, namespace: &str) -> impl Iterator<Item = schema::TypeDef<'db>> {
+        let map = self.namespace_map.borrow();
+        let mut types: Vec<_> = match map.get(namespace) {
+            Some(members) => members.types.iter().map(|(name, typ)| (*name, typ.clone())).collect(),
+            None => Vec::new()
+        };
+        types.sort_by_key(|(name, _)| *name);
+        types.into_iter().map(|(_, typ)| typ)
+    }
+
+    /// Returns the root of the namespace hierarchy, i.e. the global namespace `""`.
+    pub fn namespace_tree(&self) -> Namespace<'_, 'db> {
+        Namespace { cache: self, name: "" }
+    }
+
+    /// Returns all types whose direct base type is the given type. For generic
+    /// base types, all instantiations of the generic type definition are considered.
+    pub fn derived_types(&'db self, type_namespace: &str, type_name: &str) -> Result<impl Iterator<Item = schema::TypeDef<'db>>> {
+        self.with_reverse_index(|index| ReverseIndex::lookup(&index.derived_types, type_namespace, type_name).into_iter())
+    }
+
+    /// Returns all types that directly implement the given interface (or require it, in case
+    /// of interfaces). For generic interfaces, all instantiations are considered.
+    pub fn implementors(&'db self, type_namespace: &str, type_name: &str) -> Result<impl Iterator<Item = schema::TypeDef<'db>>> {
+        self.with_reverse_index(|index| ReverseIndex::lookup(&index.implementors, type_namespace, type_name).into_iter())
+    }
+
+    /// Returns all custom attributes of the given attribute type, in any database.
+    /// Use `CustomAttribute::parent()` to get the row the attribute is attached to.
+    pub fn attribute_usages(&'db self, type_namespace: &str, type_name: &str) -> Result<impl Iterator<Item = schema::CustomAttribute<'db>>> {
+        self.with_reverse_index(|index| ReverseIndex::lookup(&index.attribute_usages, type_namespace, type_name).into_iter())
+    }
+
+    // The reverse index is built when it is first needed, and dropped whenever a database is inserted
+    fn with_reverse_index<T, F>(&'db self, f: F) -> Result<T>
+        where F: FnOnce(&ReverseIndex<'db>) -> T
+    {
+        if self.reverse_index.borrow().is_none() {
+            let index = ReverseIndex::build(self)?;
+            *self.reverse_index.borrow_mut() = Some(index);
+        }
+        Ok(f(self.reverse_index.borrow().as_ref().expect("reverse index was just built")))
+    }
+
+    /// Returns whether `typ` is the same as `base`, derives from it or implements it.
+    /// Types are compared by namespace and name, so generic arguments are ignored.
+    pub fn is_subtype_of(&self, typ: &schema::TypeDef<'db>, base: &schema::TypeDef<'db>) -> Result<bool> {
+        let target = base.namespace_name_pair();
+        if typ.namespace_name_pair() == target {
+            return Ok(true);
+        }
+        let candidates = if base.is_interface() {
+            typ.all_interfaces(self)?
+        } else {
+            typ.base_types(self)?
+        };
+        Ok(candidates.iter().any(|t| match t {
+            schema::Type::Ref(_, r, _) => r.namespace_name_pair() == target,
+            _ => false
+        }))
+    }
 }
 
 impl<'db> IntoIterator for &'db Cache<'db> {
@@ -303,11 +461,96 @@ mod iter {
     }
 }
 
+/// A node in the namespace hierarchy of a `Cache`, e.g. `Windows.Foundation`,
+/// which is a child of `Windows`. Namespaces don't need to contain any types themselves.
+#[derive(Copy, Clone)]
+pub struct Namespace<'c, 'db> {
+    cache: &'c Cache<'db>,
+    name: &'db str
+}
+
+impl<'c, 'db> Namespace<'c, 'db> {
+    /// The full name of the namespace, e.g. `Windows.Foundation`.
+    pub fn name(&self) -> &'db str {
+        self.name
+    }
+
+    /// The last component of the namespace name, e.g. `Foundation`.
+    pub fn short_name(&self) -> &'db str {
+        match self.name.rfind('.') {
+            None => self.name,
+            Some(dot) => &self.name[dot+1 ..]
+        }
+    }
+
+    /// Returns the direct child namespaces, sorted by name.
+    pub fn children(&self) -> impl Iterator<Item = Namespace<'c, 'db>> {
+        let cache = self.cache;
+        let prefix_len = if self.name.is_empty() { 0 } else { self.name.len() + 1 };
+        let mut children = BTreeSet::new();
+        for namespace in cache.namespace_map.borrow().keys() {
+            let is_descendant = if self.name.is_empty() {
+                !namespace.is_empty()
+            } else {
+                namespace.len() > prefix_len && namespace.starts_with(self.name) && namespace.as_bytes()[self.name.len()] == b'.'
+            };
+            if is_descendant {
+                let end = namespace[prefix_len..].find('.').map_or(namespace.len(), |i| prefix_len + i);
+                children.insert(&namespace[..end]);
+            }
+        }
+        children.into_iter().map(move |name| Namespace { cache, name })
+    }
+
+    /// Returns the types in this namespace (excluding child namespaces), sorted by name.
+    pub fn types(&self) -> impl Iterator<Item = schema::TypeDef<'db>> {
+        self.cache.types_in(self.name)
+    }
+}
+
 #[derive(Default)]
 struct MemberCache<'db> {
     types: HashMap<&'db str, schema::TypeDef<'db>>,
 }
 
+// namespace -> name -> rows
+type NameMap<'db, T> = HashMap<&'db str, HashMap<&'db str, Vec<T>>>;
+
+#[derive(Default)]
+struct ReverseIndex<'db> {
+    derived_types: NameMap<'db, schema::TypeDef<'db>>,
+    implementors: NameMap<'db, schema::TypeDef<'db>>,
+    attribute_usages: NameMap<'db, schema::CustomAttribute<'db>>,
+}
+
+impl<'db> ReverseIndex<'db> {
+    fn build(cache: &'db Cache<'db>) -> Result<ReverseIndex<'db>> {
+        let mut index = ReverseIndex::default();
+        for db in cache {
+            for typ in db.table::<schema::TypeDef>() {
+                if let Some(base) = typ.extends()? {
+                    ReverseIndex::insert(&mut index.derived_types, base.definition_name_pair()?, typ.clone());
+                }
+                for imp in typ.interface_impls()? {
+                    ReverseIndex::insert(&mut index.implementors, imp.interface()?.definition_name_pair()?, typ.clone());
+                }
+            }
+            for attr in db.table::<schema::CustomAttribute>() {
+                ReverseIndex::insert(&mut index.attribute_usages, attr.namespace_name_pair(), attr.clone());
+            }
+        }
+        Ok(index)
+    }
+
+    fn insert<T>(map: &mut NameMap<'db, T>, (namespace, name): (&'db str, &'db str), row: T) {
+        map.entry(namespace).or_default().entry(name).or_default().push(row);
+    }
+
+    fn lookup<T: Clone>(map: &NameMap<'db, T>, namespace: &str, name: &str) -> Vec<T> {
+        map.get(namespace).and_then(|ns| ns.get(name)).cloned().unwrap_or_default()
+    }
+}
+
 
 pub trait ResolveToTypeDef<'db> {
     fn namespace_name_pair(&self) -> (&'db str, &'db str);
@@ -331,3 +574,144 @@ impl<'db> ResolveToTypeDef<'db> for (&'db str, &'db str) {
         *self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cache, Database};
+    use crate::core::builder::{ImageBuilder, coded, hierarchy_image, CLASS};
+    use crate::schema::TypeDef;
+
+    fn type_names<'db>(types: impl Iterator<Item = TypeDef<'db>>) -> Vec<&'db str> {
+        types.map(|t| t.type_name().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_is_subtype_of() {
+        let image = hierarchy_image();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+        let a = cache.find("N", "A`1").unwrap();
+        let c = cache.find("N", "C").unwrap();
+        let ifoo = cache.find("N", "IFoo").unwrap();
+
+        assert!(cache.is_subtype_of(&c, &c).unwrap());
+        assert!(cache.is_subtype_of(&c, &a).unwrap());
+        assert!(cache.is_subtype_of(&c, &ifoo).unwrap());
+        assert!(!cache.is_subtype_of(&a, &c).unwrap());
+        assert!(!cache.is_subtype_of(&a, &ifoo).unwrap());
+
+        let x = cache.find("N", "X").unwrap();
+        assert!(cache.is_subtype_of(&x, &a).is_err());
+    }
+
+    #[test]
+    fn test_reverse_index() {
+        use crate::schema::HasCustomAttribute;
+
+        let image = hierarchy_image();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+
+        // B`1 derives from the instantiation A<!0>, which is indexed by its generic type definition
+        assert_eq!(type_names(cache.derived_types("N", "A`1").unwrap()), ["B`1"]);
+        assert_eq!(type_names(cache.derived_types("N", "B`1").unwrap()), ["C"]);
+        assert_eq!(type_names(cache.derived_types("N", "C").unwrap()), Vec::<&str>::new());
+        assert_eq!(type_names(cache.implementors("N", "IFoo").unwrap()), ["B`1"]);
+        assert_eq!(type_names(cache.implementors("N", "Unknown").unwrap()), Vec::<&str>::new());
+
+        let usages: Vec<_> = cache.attribute_usages("N", "MarkerAttribute").unwrap().collect();
+        assert_eq!(usages.len(), 1);
+        match usages[0].parent().unwrap() {
+            HasCustomAttribute::TypeDef(t) => assert_eq!(t.type_name().unwrap(), "C"),
+            _ => panic!("expected the attribute to be applied to a TypeDef")
+        }
+    }
+
+    #[test]
+    fn test_namespace_tree() {
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        builder.type_def(CLASS, "Windows.Foundation", "Uri", 0, 1, 1);
+        builder.type_def(CLASS, "Windows.Foundation.Collections", "PropertySet", 0, 1, 1);
+        builder.type_def(CLASS, "Windows.Foundation", "Deferral", 0, 1, 1);
+        builder.type_def(CLASS, "WindowsApp", "Program", 0, 1, 1);
+        let image = builder.build();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+
+        let namespaces: Vec<_> = cache.namespaces().collect();
+        assert_eq!(namespaces, ["", "Windows.Foundation", "Windows.Foundation.Collections", "WindowsApp"]);
+        assert_eq!(type_names(cache.types_in("Windows.Foundation")), ["Deferral", "Uri"]);
+        assert_eq!(type_names(cache.types_in("Windows")), Vec::<&str>::new());
+
+        let root = cache.namespace_tree();
+        assert_eq!(root.name(), "");
+        assert_eq!(type_names(root.types()), ["<Module>"]);
+        // `Windows` contains no types itself, but is still part of the tree
+        let children: Vec<_> = root.children().map(|n| n.name()).collect();
+        assert_eq!(children, ["Windows", "WindowsApp"]);
+
+        let windows = root.children().next().unwrap();
+        assert_eq!(type_names(windows.types()), Vec::<&str>::new());
+        let foundation = windows.children().next().unwrap();
+        assert_eq!(foundation.name(), "Windows.Foundation");
+        assert_eq!(foundation.short_name(), "Foundation");
+        assert_eq!(type_names(foundation.types()), ["Deferral", "Uri"]);
+        let children: Vec<_> = foundation.children().map(|n| n.short_name()).collect();
+        assert_eq!(children, ["Collections"]);
+        assert_eq!(foundation.children().next().unwrap().children().count(), 0);
+    }
+
+    #[test]
+    fn test_find_member() {
+        use crate::schema::Member;
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        let widget = builder.type_def(CLASS, "N", "Widget", 0, 1, 1);
+        builder.type_def(CLASS, "N", "Other", 0, 2, 4);
+        builder.field(0x0001, "count", &[0x06, 0x08]);
+        builder.method(0x0086, "Run", &[0x20, 0x00, 0x01]);
+        builder.method(0x0086, "Run", &[0x20, 0x01, 0x01, 0x08]);
+        builder.method(0x0886, "get_Size", &[0x20, 0x00, 0x08]);
+
+        let changed = builder.string("Changed") as u64;
+        builder.row(0x12, &[widget as u64, 1]);
+        builder.row(0x14, &[0, changed, coded(widget, 0, 2)]);
+        let size = builder.string("Size") as u64;
+        let int32_property = builder.blob(&[0x28, 0x00, 0x08]) as u64;
+        builder.row(0x15, &[widget as u64, 1]);
+        builder.row(0x17, &[0, size, int32_property]);
+
+        let image = builder.build();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+        let widget = cache.find("N", "Widget").unwrap();
+        let other = cache.find("N", "Other").unwrap();
+
+        let overloads = widget.find_methods("Run").unwrap();
+        assert_eq!(overloads.len(), 2);
+        assert_eq!(widget.find_method("Run").unwrap(), Some(overloads[0].clone()));
+        let signature = overloads[1].signature().unwrap();
+        assert_eq!(widget.find_method_with_signature("Run", &signature).unwrap(), Some(overloads[1].clone()));
+        assert!(widget.find_method_with_signature("get_Size", &signature).unwrap().is_none());
+        assert!(other.find_method("Run").unwrap().is_none());
+        assert_eq!(widget.find_field("count").unwrap().unwrap().name().unwrap(), "count");
+        assert!(widget.find_field("Count").unwrap().is_none());
+
+        let properties: Vec<_> = widget.property_list().unwrap().map(|p| p.name().unwrap()).collect();
+        assert_eq!(properties, ["Size"]);
+        let events: Vec<_> = widget.event_list().unwrap().map(|e| e.name().unwrap()).collect();
+        assert_eq!(events, ["Changed"]);
+        assert_eq!(other.property_list().unwrap().count(), 0);
+        assert_eq!(other.event_list().unwrap().count(), 0);
+
+        assert!(matches!(cache.find_member("N.Widget", "count").unwrap(), Some(Member::Field(_))));
+        assert!(matches!(cache.find_member("N.Widget", "Run").unwrap(), Some(Member::Method(m)) if m == overloads[0]));
+        assert!(matches!(cache.find_member("N.Widget", "Size").unwrap(), Some(Member::Property(_))));
+        assert!(matches!(cache.find_member("N.Widget", "Changed").unwrap(), Some(Member::Event(_))));
+        assert!(cache.find_member("N.Widget", "Missing").unwrap().is_none());
+        assert!(cache.find_member("N.Missing", "Run").unwrap().is_none());
+        assert_eq!(cache.find_member_with_signature("N.Widget", "Run", &signature).unwrap(), Some(overloads[1].clone()));
+    }
+}
@@ -12,6 +12,20 @@ mod signatures;
 pub use signatures::*;
 mod custom_attributes;
 pub use custom_attributes::*;
+#[cfg(feature = "derive")]
+pub use climeta_derive::FromCustomAttribute;
+mod method_body;
+pub use method_body::*;
+mod cil;
+pub use cil::*;
+mod assembly_name;
+pub use assembly_name::*;
+mod layout;
+pub use layout::*;
+mod marshal;
+pub use marshal::*;
+mod security;
+pub use security::*;
 
 macro_rules! table_kind {
     ($ty:ident [$($colty:ty),+]) => {
@@ -92,7 +106,7 @@ pub mod marker {
 macro_rules! coded_index {
     ($name:ident[$bits:tt] { $($n:tt => $ty:ident),+ }) => {
 
-        #[derive(Clone)]
+        #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub enum $name<'db> {
             $($ty(rows::$ty<'db>)),+
         }
@@ -277,6 +291,69 @@ coded_index! {
     }
 }
 
+// Display impls write `<invalid>` for anything that can not be decoded, because returning
+// `fmt::Error` would make `to_string()` panic on malformed metadata
+pub(crate) fn fmt_or_invalid<F>(f: &mut fmt::Formatter, inner: F) -> fmt::Result
+    where F: FnOnce(&mut fmt::Formatter) -> Result<fmt::Result>
+{
+    match inner(f) {
+        Ok(result) => result,
+        Err(_) => write!(f, "<invalid>")
+    }
+}
+
+impl<'db> fmt::Display for TypeDefOrRef<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TypeDefOrRef::TypeDef(t) => write!(f, "{}", t),
+            TypeDefOrRef::TypeRef(t) => write!(f, "{}", t),
+            TypeDefOrRef::TypeSpec(t) => write!(f, "{}", t)
+        }
+    }
+}
+
+impl<'db> fmt::Display for MemberRefParent<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.15.4.2.1 (a vararg call site references the MethodDef, which is qualified by its type)
+        match self {
+            MemberRefParent::TypeDef(t) => write!(f, "{}", t),
+            MemberRefParent::TypeRef(t) => write!(f, "{}", t),
+            MemberRefParent::ModuleRef(m) => fmt_or_invalid(f, |f| Ok(write!(f, "[.module {}]", m.name()?))),
+            MemberRefParent::MethodDef(m) => fmt_or_invalid(f, |f| Ok(write!(f, "{}", m.parent()?))),
+            MemberRefParent::TypeSpec(t) => write!(f, "{}", t)
+        }
+    }
+}
+
+impl<'db> TypeDefOrRef<'db> {
+    // ECMA-335, II.22 (the table number is stored in the high byte of a metadata token)
+    pub(crate) fn from_token(token: u32, db: &'db Database<'db>) -> Result<Option<TypeDefOrRef<'db>>> {
+        let row = token & 0x00ff_ffff;
+        if row == 0 {
+            return Ok(None);
+        }
+        Ok(Some(match token >> 24 {
+            0x01 => TypeDefOrRef::TypeRef(db.get_table::<TypeRef>().get_row(row - 1)?),
+            0x02 => TypeDefOrRef::TypeDef(db.get_table::<TypeDef>().get_row(row - 1)?),
+            0x1b => TypeDefOrRef::TypeSpec(db.get_table::<TypeSpec>().get_row(row - 1)?),
+            _ => return Err("Token does not reference a TypeDef, TypeRef or TypeSpec".into())
+        }))
+    }
+}
+
+impl<'db> TypeDefOrRef<'db> {
+    // Like `namespace_name_pair`, but for a generic instantiation in a TypeSpec
+    // this returns the namespace and name of the generic type definition
+    pub(crate) fn definition_name_pair(&self) -> Result<(&'db str, &'db str)> {
+        Ok(match self {
+            TypeDefOrRef::TypeSpec(s) => match s.signature()? {
+                TypeSpecSig::GenericInst(_, t, _) => t.namespace_name_pair()
+            },
+            _ => self.namespace_name_pair()
+        })
+    }
+}
+
 impl<'db> ResolveToTypeDef<'db> for TypeDefOrRef<'db> {
     fn namespace_name_pair(&self) -> (&'db str, &'db str) {
         match self {
@@ -295,6 +372,15 @@ impl<'db> ResolveToTypeDef<'db> for TypeDefOrRef<'db> {
     }
 }
 
+/// A named member of a TypeDef (see `Cache::find_member`).
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Member<'db> {
+    Field(Field<'db>),
+    Method(MethodDef<'db>),
+    Property(Property<'db>),
+    Event(Event<'db>),
+}
+
 #[repr(u16)]
 #[derive(FromPrimitive, ToPrimitive)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -331,24 +417,81 @@ pub enum PrimitiveValue {
     Float64(f64),
 }
 
-impl<'db> fmt::Debug for PrimitiveValue {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl PrimitiveValue {
+    /// The ILAsm name of the value's type (ECMA-335, II.7.1).
+    pub fn type_name(&self) -> &'static str {
         use PrimitiveValue::*;
         match self {
-            Boolean(v) => write!(f, "bool({})", v),
-            Char(v) => write!(f, "char({})", v),
-            Int8(v) => write!(f, "int8({})", v),
-            UInt8(v) => write!(f, "unsigned int8({})", v),
-            Int16(v) => write!(f, "int16({})", v),
-            UInt16(v) => write!(f, "unsigned int16({})", v),
-            Int32(v) => write!(f, "int32({})", v),
-            UInt32(v) => write!(f, "unsigned int32({})", v),
-            Int64(v) => write!(f, "int64({})", v),
-            UInt64(v) => write!(f, "unsigned int64({})", v),
-            Float32(v) => write!(f, "float32({})", v),
-            Float64(v) => write!(f, "float64({})", v)
+            Boolean(_) => "bool",
+            Char(_) => "char",
+            Int8(_) => "int8",
+            UInt8(_) => "unsigned int8",
+            Int16(_) => "int16",
+            UInt16(_) => "unsigned int16",
+            Int32(_) => "int32",
+            UInt32(_) => "unsigned int32",
+            Int64(_) => "int64",
+            UInt64(_) => "unsigned int64",
+            Float32(_) => "float32",
+            Float64(_) => "float64"
         }
     }
+
+    // Writes the value without the surrounding type name
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use PrimitiveValue::*;
+        match self {
+            Boolean(v) => write!(f, "{}", v)?,
+            Char(v) => write!(f, "{}", v)?,
+            Int8(v) => write!(f, "{}", v)?,
+            UInt8(v) => write!(f, "{}", v)?,
+            Int16(v) => write!(f, "{}", v)?,
+            UInt16(v) => write!(f, "{}", v)?,
+            Int32(v) => write!(f, "{}", v)?,
+            UInt32(v) => write!(f, "{}", v)?,
+            Int64(v) => write!(f, "{}", v)?,
+            UInt64(v) => write!(f, "{}", v)?,
+            // ILAsm has no literals for infinity and NaN, so the bit pattern is written instead
+            Float32(v) if !v.is_finite() => write!(f, "0x{:08x}", v.to_bits())?,
+            Float64(v) if !v.is_finite() => write!(f, "0x{:016x}", v.to_bits())?,
+            Float32(v) => write!(f, "{:?}", v)?,
+            Float64(v) => write!(f, "{:?}", v)?
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PrimitiveValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.16.2
+        write!(f, "{}(", self.type_name())?;
+        self.fmt_value(f)?;
+        write!(f, ")")
+    }
+}
+
+impl fmt::Debug for PrimitiveValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+// Writes a string literal in ILAsm syntax, escaping the quote character, backslashes and control characters
+pub(crate) fn fmt_quoted(s: &str, quote: char, f: &mut fmt::Formatter) -> fmt::Result {
+    use std::fmt::Write;
+    f.write_char(quote)?;
+    for c in s.chars() {
+        match c {
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c == quote => { f.write_char('\\')?; f.write_char(c)?; },
+            c if c.is_control() => write!(f, "\\{:03o}", c as u32)?,
+            c => f.write_char(c)?
+        }
+    }
+    f.write_char(quote)
 }
 
 // ECMA-335, II.16.2
@@ -359,17 +502,33 @@ pub enum FieldInit<'db> {
     NullRef
 }
 
-impl<'db> fmt::Debug for FieldInit<'db> {
+impl<'db> fmt::Display for FieldInit<'db> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use FieldInit::*;
         match self {
-            Primitive(p) => write!(f, "{:?}", p),
-            String(Some(v)) => write!(f, "{:?}", v),
+            Primitive(p) => write!(f, "{}", p),
+            String(Some(v)) => fmt_quoted(v, '"', f),
             NullRef | String(None) => write!(f, "nullref"),
         }
     }
 }
 
+impl<'db> fmt::Debug for FieldInit<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// An interface method and the method of a class that implements it (see `TypeDef::interface_map`).
+#[derive(Clone)]
+pub struct InterfaceMethodImpl<'db> {
+    /// The interface type, with generic arguments substituted.
+    pub interface: Type<'db>,
+    pub declaration: MethodDef<'db>,
+    /// `None` if no implementing method was found.
+    pub implementation: Option<MethodDef<'db>>,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TypeCategory {
     Interface,
@@ -0,0 +1,184 @@
+use std::fmt;
+#[cfg(feature = "strong-name")]
+use crate::Result;
+
+// ECMA-335, II.22.2 (MajorVersion, MinorVersion, BuildNumber, RevisionNumber)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+    pub build: u16,
+    pub revision: u16,
+}
+
+impl Version {
+    // The four version columns are adjacent, so they are read as a single 8-byte value
+    pub(crate) fn from_u64(value: u64) -> Version {
+        Version {
+            major: value as u16,
+            minor: (value >> 16) as u16,
+            build: (value >> 32) as u16,
+            revision: (value >> 48) as u16
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}.{}", self.major, self.minor, self.build, self.revision)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PublicKeyOrToken<'db> {
+    PublicKey(&'db [u8]),
+    Token(&'db [u8]),
+}
+
+impl<'db> PublicKeyOrToken<'db> {
+    /// Returns the public key token, computing it from the full public key if necessary.
+    #[cfg(feature = "strong-name")]
+    pub fn token(&self) -> Result<[u8; 8]> {
+        match *self {
+            PublicKeyOrToken::PublicKey(key) => Ok(public_key_token(key)),
+            PublicKeyOrToken::Token(token) => {
+                use std::convert::TryInto;
+                token.try_into().map_err(|_| "Public key token must be 8 bytes long".into())
+            }
+        }
+    }
+}
+
+/// Computes the public key token of a strong-named assembly, which consists of
+/// the last 8 bytes of the SHA-1 hash of the public key in reverse order.
+#[cfg(feature = "strong-name")]
+pub fn public_key_token(public_key: &[u8]) -> [u8; 8] {
+    let hash = sha1_smol::Sha1::from(public_key).digest().bytes();
+    let mut token = [0u8; 8];
+    for (t, h) in token.iter_mut().zip(hash.iter().rev()) {
+        *t = *h;
+    }
+    token
+}
+
+/// The identity of an assembly, as returned by `Assembly::identity()` and `AssemblyRef::identity()`.
+///
+/// Formats as a display name, e.g. `mscorlib, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089`.
+/// With the `strong-name` feature, the token of a full public key is computed. Without it, a full
+/// public key is written as `PublicKey=<hex>`, which is not a valid display name and cannot be
+/// parsed back into an assembly name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AssemblyName<'db> {
+    pub name: &'db str,
+    pub version: Version,
+    pub culture: &'db str, // empty for culture-neutral assemblies
+    pub public_key: Option<PublicKeyOrToken<'db>>,
+}
+
+impl<'db> AssemblyName<'db> {
+    #[cfg(feature = "strong-name")]
+    pub fn public_key_token(&self) -> Result<Option<[u8; 8]>> {
+        match self.public_key {
+            Some(ref key) => Ok(Some(key.token()?)),
+            None => Ok(None)
+        }
+    }
+
+    /// Returns the display name with a `PublicKeyToken`, computing the token from a full
+    /// public key if necessary. Unlike `to_string()`, fails if the token is not 8 bytes long.
+    #[cfg(feature = "strong-name")]
+    pub fn display_name(&self) -> Result<String> {
+        let token = self.public_key_token()?;
+        let name = AssemblyName { public_key: token.as_ref().map(|t| PublicKeyOrToken::Token(t)), ..*self };
+        Ok(name.to_string())
+    }
+
+    /// Checks whether a reference with this name binds to an assembly with the `other` name,
+    /// i.e. whether name, version, culture and public key token are equal. Names and cultures
+    /// are compared case-insensitively, and full public keys are compared by their tokens.
+    #[cfg(feature = "strong-name")]
+    pub fn same_identity(&self, other: &AssemblyName) -> Result<bool> {
+        Ok(self.name.eq_ignore_ascii_case(other.name)
+            && self.version == other.version
+            && self.culture.eq_ignore_ascii_case(other.culture)
+            && self.public_key_token()? == other.public_key_token()?)
+    }
+}
+
+impl<'db> fmt::Display for AssemblyName<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, Version={}, Culture={}", self.name, self.version,
+               if self.culture.is_empty() { "neutral" } else { self.culture })?;
+        match self.public_key {
+            None => write!(f, ", PublicKeyToken=null"),
+            Some(PublicKeyOrToken::Token(token)) => {
+                write!(f, ", PublicKeyToken=")?;
+                fmt_hex(token, f)
+            },
+            #[cfg(feature = "strong-name")]
+            Some(PublicKeyOrToken::PublicKey(key)) => {
+                write!(f, ", PublicKeyToken=")?;
+                fmt_hex(&public_key_token(key), f)
+            },
+            #[cfg(not(feature = "strong-name"))]
+            Some(PublicKeyOrToken::PublicKey(key)) => {
+                write!(f, ", PublicKey=")?;
+                fmt_hex(key, f)
+            }
+        }
+    }
+}
+
+fn fmt_hex(bytes: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
+    for b in bytes {
+        write!(f, "{:02x}", b)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Version, AssemblyName, PublicKeyOrToken};
+
+    #[test]
+    fn test_display() {
+        let version = Version::from_u64(0x0004_0003_0002_0001);
+        assert_eq!(version, Version { major: 1, minor: 2, build: 3, revision: 4 });
+        assert_eq!(version.to_string(), "1.2.3.4");
+
+        let token = [0xb7, 0x7a, 0x5c, 0x56, 0x19, 0x34, 0xe0, 0x89];
+        let name = AssemblyName { name: "mscorlib", version, culture: "", public_key: Some(PublicKeyOrToken::Token(&token)) };
+        assert_eq!(name.to_string(), "mscorlib, Version=1.2.3.4, Culture=neutral, PublicKeyToken=b77a5c561934e089");
+        let name = AssemblyName { name: "Foo", version, culture: "de-DE", public_key: None };
+        assert_eq!(name.to_string(), "Foo, Version=1.2.3.4, Culture=de-DE, PublicKeyToken=null");
+    }
+
+    #[cfg(not(feature = "strong-name"))]
+    #[test]
+    fn test_display_public_key() {
+        let key = [0x00, 0x24, 0x00, 0x00];
+        let version = Version { major: 1, minor: 2, build: 3, revision: 4 };
+        let name = AssemblyName { name: "Foo", version, culture: "de-DE", public_key: Some(PublicKeyOrToken::PublicKey(&key)) };
+        assert_eq!(name.to_string(), "Foo, Version=1.2.3.4, Culture=de-DE, PublicKey=00240000");
+    }
+
+    #[cfg(feature = "strong-name")]
+    #[test]
+    fn test_public_key_token() {
+        // the ECMA standard public key (ECMA-335, II.6.2.1.3)
+        let ecma_key = [0u8, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(super::public_key_token(&ecma_key), [0xb7, 0x7a, 0x5c, 0x56, 0x19, 0x34, 0xe0, 0x89]);
+
+        let version = Version { major: 4, minor: 0, build: 0, revision: 0 };
+        let def = AssemblyName { name: "mscorlib", version, culture: "", public_key: Some(PublicKeyOrToken::PublicKey(&ecma_key)) };
+        assert_eq!(def.to_string(), "mscorlib, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089");
+        assert_eq!(def.display_name().unwrap(), "mscorlib, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089");
+
+        let token = [0xb7, 0x7a, 0x5c, 0x56, 0x19, 0x34, 0xe0, 0x89];
+        let reference = AssemblyName { name: "MSCORLIB", version, culture: "", public_key: Some(PublicKeyOrToken::Token(&token)) };
+        assert!(reference.same_identity(&def).unwrap());
+        let reference = AssemblyName { public_key: None, ..reference };
+        assert!(!reference.same_identity(&def).unwrap());
+        assert!(PublicKeyOrToken::Token(&token[1..]).token().is_err());
+    }
+}
@@ -0,0 +1,674 @@
+use byteorder::{ReadBytesExt, LittleEndian};
+
+use crate::Result;
+use crate::core::db::Database;
+use super::{TypeDefOrRef, MethodDef, MemberRef, MethodSpec, Field, StandAloneSig};
+
+// ECMA-335, III.1.2.1 and VI.C.2
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OperandType {
+    InlineNone,
+    ShortInlineI,
+    InlineI,
+    InlineI8,
+    ShortInlineR,
+    InlineR,
+    ShortInlineVar,
+    InlineVar,
+    ShortInlineBrTarget,
+    InlineBrTarget,
+    InlineSwitch,
+    InlineMethod,
+    InlineField,
+    InlineType,
+    InlineTok,
+    InlineString,
+    InlineSig,
+}
+
+macro_rules! opcodes {
+    ($($name:ident = $value:literal, $mnemonic:literal, $operand:ident;)+) => {
+        #[repr(u16)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Opcode {
+            $($name = $value),+
+        }
+
+        impl Opcode {
+            fn from_value(value: u16) -> Option<Opcode> {
+                match value {
+                    $($value => Some(Opcode::$name),)+
+                    _ => None
+                }
+            }
+
+            /// The numeric value of the opcode. Two-byte opcodes have the high byte set to `0xFE`.
+            pub fn value(&self) -> u16 {
+                *self as u16
+            }
+
+            /// The mnemonic of the opcode in ILAsm syntax (e.g. `ldc.i4.s`).
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Opcode::$name => $mnemonic),+
+                }
+            }
+
+            pub fn operand_type(&self) -> OperandType {
+                match self {
+                    $(Opcode::$name => OperandType::$operand),+
+                }
+            }
+        }
+    }
+}
+
+opcodes! {
+    Nop = 0x00, "nop", InlineNone;
+    Break = 0x01, "break", InlineNone;
+    Ldarg0 = 0x02, "ldarg.0", InlineNone;
+    Ldarg1 = 0x03, "ldarg.1", InlineNone;
+    Ldarg2 = 0x04, "ldarg.2", InlineNone;
+    Ldarg3 = 0x05, "ldarg.3", InlineNone;
+    Ldloc0 = 0x06, "ldloc.0", InlineNone;
+    Ldloc1 = 0x07, "ldloc.1", InlineNone;
+    Ldloc2 = 0x08, "ldloc.2", InlineNone;
+    Ldloc3 = 0x09, "ldloc.3", InlineNone;
+    Stloc0 = 0x0a, "stloc.0", InlineNone;
+    Stloc1 = 0x0b, "stloc.1", InlineNone;
+    Stloc2 = 0x0c, "stloc.2", InlineNone;
+    Stloc3 = 0x0d, "stloc.3", InlineNone;
+    LdargS = 0x0e, "ldarg.s", ShortInlineVar;
+    LdargaS = 0x0f, "ldarga.s", ShortInlineVar;
+    StargS = 0x10, "starg.s", ShortInlineVar;
+    LdlocS = 0x11, "ldloc.s", ShortInlineVar;
+    LdlocaS = 0x12, "ldloca.s", ShortInlineVar;
+    StlocS = 0x13, "stloc.s", ShortInlineVar;
+    Ldnull = 0x14, "ldnull", InlineNone;
+    LdcI4M1 = 0x15, "ldc.i4.m1", InlineNone;
+    LdcI40 = 0x16, "ldc.i4.0", InlineNone;
+    LdcI41 = 0x17, "ldc.i4.1", InlineNone;
+    LdcI42 = 0x18, "ldc.i4.2", InlineNone;
+    LdcI43 = 0x19, "ldc.i4.3", InlineNone;
+    LdcI44 = 0x1a, "ldc.i4.4", InlineNone;
+    LdcI45 = 0x1b, "ldc.i4.5", InlineNone;
+    LdcI46 = 0x1c, "ldc.i4.6", InlineNone;
+    LdcI47 = 0x1d, "ldc.i4.7", InlineNone;
+    LdcI48 = 0x1e, "ldc.i4.8", InlineNone;
+    LdcI4S = 0x1f, "ldc.i4.s", ShortInlineI;
+    LdcI4 = 0x20, "ldc.i4", InlineI;
+    LdcI8 = 0x21, "ldc.i8", InlineI8;
+    LdcR4 = 0x22, "ldc.r4", ShortInlineR;
+    LdcR8 = 0x23, "ldc.r8", InlineR;
+    Dup = 0x25, "dup", InlineNone;
+    Pop = 0x26, "pop", InlineNone;
+    Jmp = 0x27, "jmp", InlineMethod;
+    Call = 0x28, "call", InlineMethod;
+    Calli = 0x29, "calli", InlineSig;
+    Ret = 0x2a, "ret", InlineNone;
+    BrS = 0x2b, "br.s", ShortInlineBrTarget;
+    BrfalseS = 0x2c, "brfalse.s", ShortInlineBrTarget;
+    BrtrueS = 0x2d, "brtrue.s", ShortInlineBrTarget;
+    BeqS = 0x2e, "beq.s", ShortInlineBrTarget;
+    BgeS = 0x2f, "bge.s", ShortInlineBrTarget;
+    BgtS = 0x30, "bgt.s", ShortInlineBrTarget;
+    BleS = 0x31, "ble.s", ShortInlineBrTarget;
+    BltS = 0x32, "blt.s", ShortInlineBrTarget;
+    BneUnS = 0x33, "bne.un.s", ShortInlineBrTarget;
+    BgeUnS = 0x34, "bge.un.s", ShortInlineBrTarget;
+    BgtUnS = 0x35, "bgt.un.s", ShortInlineBrTarget;
+    BleUnS = 0x36, "ble.un.s", ShortInlineBrTarget;
+    BltUnS = 0x37, "blt.un.s", ShortInlineBrTarget;
+    Br = 0x38, "br", InlineBrTarget;
+    Brfalse = 0x39, "brfalse", InlineBrTarget;
+    Brtrue = 0x3a, "brtrue", InlineBrTarget;
+    Beq = 0x3b, "beq", InlineBrTarget;
+    Bge = 0x3c, "bge", InlineBrTarget;
+    Bgt = 0x3d, "bgt", InlineBrTarget;
+    Ble = 0x3e, "ble", InlineBrTarget;
+    Blt = 0x3f, "blt", InlineBrTarget;
+    BneUn = 0x40, "bne.un", InlineBrTarget;
+    BgeUn = 0x41, "bge.un", InlineBrTarget;
+    BgtUn = 0x42, "bgt.un", InlineBrTarget;
+    BleUn = 0x43, "ble.un", InlineBrTarget;
+    BltUn = 0x44, "blt.un", InlineBrTarget;
+    Switch = 0x45, "switch", InlineSwitch;
+    LdindI1 = 0x46, "ldind.i1", InlineNone;
+    LdindU1 = 0x47, "ldind.u1", InlineNone;
+    LdindI2 = 0x48, "ldind.i2", InlineNone;
+    LdindU2 = 0x49, "ldind.u2", InlineNone;
+    LdindI4 = 0x4a, "ldind.i4", InlineNone;
+    LdindU4 = 0x4b, "ldind.u4", InlineNone;
+    LdindI8 = 0x4c, "ldind.i8", InlineNone;
+    LdindI = 0x4d, "ldind.i", InlineNone;
+    LdindR4 = 0x4e, "ldind.r4", InlineNone;
+    LdindR8 = 0x4f, "ldind.r8", InlineNone;
+    LdindRef = 0x50, "ldind.ref", InlineNone;
+    StindRef = 0x51, "stind.ref", InlineNone;
+    StindI1 = 0x52, "stind.i1", InlineNone;
+    StindI2 = 0x53, "stind.i2", InlineNone;
+    StindI4 = 0x54, "stind.i4", InlineNone;
+    StindI8 = 0x55, "stind.i8", InlineNone;
+    StindR4 = 0x56, "stind.r4", InlineNone;
+    StindR8 = 0x57, "stind.r8", InlineNone;
+    Add = 0x58, "add", InlineNone;
+    Sub = 0x59, "sub", InlineNone;
+    Mul = 0x5a, "mul", InlineNone;
+    Div = 0x5b, "div", InlineNone;
+    DivUn = 0x5c, "div.un", InlineNone;
+    Rem = 0x5d, "rem", InlineNone;
+    RemUn = 0x5e, "rem.un", InlineNone;
+    And = 0x5f, "and", InlineNone;
+    Or = 0x60, "or", InlineNone;
+    Xor = 0x61, "xor", InlineNone;
+    Shl = 0x62, "shl", InlineNone;
+    Shr = 0x63, "shr", InlineNone;
+    ShrUn = 0x64, "shr.un", InlineNone;
+    Neg = 0x65, "neg", InlineNone;
+    Not = 0x66, "not", InlineNone;
+    ConvI1 = 0x67, "conv.i1", InlineNone;
+    ConvI2 = 0x68, "conv.i2", InlineNone;
+    ConvI4 = 0x69, "conv.i4", InlineNone;
+    ConvI8 = 0x6a, "conv.i8", InlineNone;
+    ConvR4 = 0x6b, "conv.r4", InlineNone;
+    ConvR8 = 0x6c, "conv.r8", InlineNone;
+    ConvU4 = 0x6d, "conv.u4", InlineNone;
+    ConvU8 = 0x6e, "conv.u8", InlineNone;
+    Callvirt = 0x6f, "callvirt", InlineMethod;
+    Cpobj = 0x70, "cpobj", InlineType;
+    Ldobj = 0x71, "ldobj", InlineType;
+    Ldstr = 0x72, "ldstr", InlineString;
+    Newobj = 0x73, "newobj", InlineMethod;
+    Castclass = 0x74, "castclass", InlineType;
+    Isinst = 0x75, "isinst", InlineType;
+    ConvRUn = 0x76, "conv.r.un", InlineNone;
+    Unbox = 0x79, "unbox", InlineType;
+    Throw = 0x7a, "throw", InlineNone;
+    Ldfld = 0x7b, "ldfld", InlineField;
+    Ldflda = 0x7c, "ldflda", InlineField;
+    Stfld = 0x7d, "stfld", InlineField;
+    Ldsfld = 0x7e, "ldsfld", InlineField;
+    Ldsflda = 0x7f, "ldsflda", InlineField;
+    Stsfld = 0x80, "stsfld", InlineField;
+    Stobj = 0x81, "stobj", InlineType;
+    ConvOvfI1Un = 0x82, "conv.ovf.i1.un", InlineNone;
+    ConvOvfI2Un = 0x83, "conv.ovf.i2.un", InlineNone;
+    ConvOvfI4Un = 0x84, "conv.ovf.i4.un", InlineNone;
+    ConvOvfI8Un = 0x85, "conv.ovf.i8.un", InlineNone;
+    ConvOvfU1Un = 0x86, "conv.ovf.u1.un", InlineNone;
+    ConvOvfU2Un = 0x87, "conv.ovf.u2.un", InlineNone;
+    ConvOvfU4Un = 0x88, "conv.ovf.u4.un", InlineNone;
+    ConvOvfU8Un = 0x89, "conv.ovf.u8.un", InlineNone;
+    ConvOvfIUn = 0x8a, "conv.ovf.i.un", InlineNone;
+    ConvOvfUUn = 0x8b, "conv.ovf.u.un", InlineNone;
+    Box = 0x8c, "box", InlineType;
+    Newarr = 0x8d, "newarr", InlineType;
+    Ldlen = 0x8e, "ldlen", InlineNone;
+    Ldelema = 0x8f, "ldelema", InlineType;
+    LdelemI1 = 0x90, "ldelem.i1", InlineNone;
+    LdelemU1 = 0x91, "ldelem.u1", InlineNone;
+    LdelemI2 = 0x92, "ldelem.i2", InlineNone;
+    LdelemU2 = 0x93, "ldelem.u2", InlineNone;
+    LdelemI4 = 0x94, "ldelem.i4", InlineNone;
+    LdelemU4 = 0x95, "ldelem.u4", InlineNone;
+    LdelemI8 = 0x96, "ldelem.i8", InlineNone;
+    LdelemI = 0x97, "ldelem.i", InlineNone;
+    LdelemR4 = 0x98, "ldelem.r4", InlineNone;
+    LdelemR8 = 0x99, "ldelem.r8", InlineNone;
+    LdelemRef = 0x9a, "ldelem.ref", InlineNone;
+    StelemI = 0x9b, "stelem.i", InlineNone;
+    StelemI1 = 0x9c, "stelem.i1", InlineNone;
+    StelemI2 = 0x9d, "stelem.i2", InlineNone;
+    StelemI4 = 0x9e, "stelem.i4", InlineNone;
+    StelemI8 = 0x9f, "stelem.i8", InlineNone;
+    StelemR4 = 0xa0, "stelem.r4", InlineNone;
+    StelemR8 = 0xa1, "stelem.r8", InlineNone;
+    StelemRef = 0xa2, "stelem.ref", InlineNone;
+    Ldelem = 0xa3, "ldelem", InlineType;
+    Stelem = 0xa4, "stelem", InlineType;
+    UnboxAny = 0xa5, "unbox.any", InlineType;
+    ConvOvfI1 = 0xb3, "conv.ovf.i1", InlineNone;
+    ConvOvfU1 = 0xb4, "conv.ovf.u1", InlineNone;
+    ConvOvfI2 = 0xb5, "conv.ovf.i2", InlineNone;
+    ConvOvfU2 = 0xb6, "conv.ovf.u2", InlineNone;
+    ConvOvfI4 = 0xb7, "conv.ovf.i4", InlineNone;
+    ConvOvfU4 = 0xb8, "conv.ovf.u4", InlineNone;
+    ConvOvfI8 = 0xb9, "conv.ovf.i8", InlineNone;
+    ConvOvfU8 = 0xba, "conv.ovf.u8", InlineNone;
+    Refanyval = 0xc2, "refanyval", InlineType;
+    Ckfinite = 0xc3, "ckfinite", InlineNone;
+    Mkrefany = 0xc6, "mkrefany", InlineType;
+    Ldtoken = 0xd0, "ldtoken", InlineTok;
+    ConvU2 = 0xd1, "conv.u2", InlineNone;
+    ConvU1 = 0xd2, "conv.u1", InlineNone;
+    ConvI = 0xd3, "conv.i", InlineNone;
+    ConvOvfI = 0xd4, "conv.ovf.i", InlineNone;
+    ConvOvfU = 0xd5, "conv.ovf.u", InlineNone;
+    AddOvf = 0xd6, "add.ovf", InlineNone;
+    AddOvfUn = 0xd7, "add.ovf.un", InlineNone;
+    MulOvf = 0xd8, "mul.ovf", InlineNone;
+    MulOvfUn = 0xd9, "mul.ovf.un", InlineNone;
+    SubOvf = 0xda, "sub.ovf", InlineNone;
+    SubOvfUn = 0xdb, "sub.ovf.un", InlineNone;
+    Endfinally = 0xdc, "endfinally", InlineNone;
+    Leave = 0xdd, "leave", InlineBrTarget;
+    LeaveS = 0xde, "leave.s", ShortInlineBrTarget;
+    StindI = 0xdf, "stind.i", InlineNone;
+    ConvU = 0xe0, "conv.u", InlineNone;
+    Arglist = 0xfe00, "arglist", InlineNone;
+    Ceq = 0xfe01, "ceq", InlineNone;
+    Cgt = 0xfe02, "cgt", InlineNone;
+    CgtUn = 0xfe03, "cgt.un", InlineNone;
+    Clt = 0xfe04, "clt", InlineNone;
+    CltUn = 0xfe05, "clt.un", InlineNone;
+    Ldftn = 0xfe06, "ldftn", InlineMethod;
+    Ldvirtftn = 0xfe07, "ldvirtftn", InlineMethod;
+    Ldarg = 0xfe09, "ldarg", InlineVar;
+    Ldarga = 0xfe0a, "ldarga", InlineVar;
+    Starg = 0xfe0b, "starg", InlineVar;
+    Ldloc = 0xfe0c, "ldloc", InlineVar;
+    Ldloca = 0xfe0d, "ldloca", InlineVar;
+    Stloc = 0xfe0e, "stloc", InlineVar;
+    Localloc = 0xfe0f, "localloc", InlineNone;
+    Endfilter = 0xfe11, "endfilter", InlineNone;
+    Unaligned = 0xfe12, "unaligned.", ShortInlineI;
+    Volatile = 0xfe13, "volatile.", InlineNone;
+    Tail = 0xfe14, "tail.", InlineNone;
+    Initobj = 0xfe15, "initobj", InlineType;
+    Constrained = 0xfe16, "constrained.", InlineType;
+    Cpblk = 0xfe17, "cpblk", InlineNone;
+    Initblk = 0xfe18, "initblk", InlineNone;
+    No = 0xfe19, "no.", ShortInlineI;
+    Rethrow = 0xfe1a, "rethrow", InlineNone;
+    Sizeof = 0xfe1c, "sizeof", InlineType;
+    Refanytype = 0xfe1d, "refanytype", InlineNone;
+    Readonly = 0xfe1e, "readonly.", InlineNone;
+}
+
+/// The target of a `call`, `callvirt`, `newobj`, `jmp`, `ldftn` or `ldvirtftn` instruction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MethodToken<'db> {
+    MethodDef(MethodDef<'db>),
+    MemberRef(MemberRef<'db>),
+    MethodSpec(MethodSpec<'db>),
+}
+
+/// The target of a field access instruction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FieldToken<'db> {
+    Field(Field<'db>),
+    MemberRef(MemberRef<'db>),
+}
+
+/// The operand of a `ldtoken` instruction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Token<'db> {
+    Type(TypeDefOrRef<'db>),
+    Method(MethodToken<'db>),
+    Field(FieldToken<'db>),
+}
+
+#[derive(Clone, Debug)]
+pub enum Operand<'db> {
+    None,
+    Int8(i8),
+    Int32(i32),
+    Int64(i64),
+    Float32(f32),
+    Float64(f64),
+    Var(u16), // argument or local variable index
+    BranchTarget(u32), // absolute offset within the method body
+    Switch(Box<[u32]>), // absolute offsets within the method body
+    Method(MethodToken<'db>),
+    Field(FieldToken<'db>),
+    Type(TypeDefOrRef<'db>),
+    Token(Token<'db>),
+    String(String),
+    Signature(StandAloneSig<'db>),
+}
+
+// Operand as stored in the instruction stream, before resolving any tokens
+#[derive(Clone, Debug, PartialEq)]
+enum RawOperand {
+    None,
+    Int8(i8),
+    Int32(i32),
+    Int64(i64),
+    Float32(f32),
+    Float64(f64),
+    Var(u16),
+    BranchTarget(u32),
+    Switch(Box<[u32]>),
+    Token(u32),
+}
+
+fn branch_target(next: usize, delta: i32) -> Result<u32> {
+    let target = next as i64 + delta as i64;
+    if target < 0 || target > u32::MAX as i64 {
+        return Err("Invalid branch target".into());
+    }
+    Ok(target as u32)
+}
+
+fn decode_raw(code: &[u8], offset: usize) -> Result<(Opcode, RawOperand, usize)> {
+    let mut cur = &code[offset..];
+    let first = cur.read_u8()?;
+    let value = if first == 0xfe {
+        0xfe00 | cur.read_u8()? as u16
+    } else {
+        first as u16
+    };
+    let opcode = Opcode::from_value(value).ok_or(crate::DecodeError("Invalid CIL opcode"))?;
+
+    let operand = match opcode.operand_type() {
+        OperandType::InlineNone => RawOperand::None,
+        OperandType::ShortInlineI => RawOperand::Int8(cur.read_i8()?),
+        OperandType::InlineI => RawOperand::Int32(cur.read_i32::<LittleEndian>()?),
+        OperandType::InlineI8 => RawOperand::Int64(cur.read_i64::<LittleEndian>()?),
+        OperandType::ShortInlineR => RawOperand::Float32(cur.read_f32::<LittleEndian>()?),
+        OperandType::InlineR => RawOperand::Float64(cur.read_f64::<LittleEndian>()?),
+        OperandType::ShortInlineVar => RawOperand::Var(cur.read_u8()? as u16),
+        OperandType::InlineVar => RawOperand::Var(cur.read_u16::<LittleEndian>()?),
+        OperandType::ShortInlineBrTarget => {
+            let delta = cur.read_i8()? as i32;
+            RawOperand::BranchTarget(branch_target(code.len() - cur.len(), delta)?)
+        },
+        OperandType::InlineBrTarget => {
+            let delta = cur.read_i32::<LittleEndian>()?;
+            RawOperand::BranchTarget(branch_target(code.len() - cur.len(), delta)?)
+        },
+        OperandType::InlineSwitch => {
+            let count = cur.read_u32::<LittleEndian>()? as usize;
+            if count > cur.len() / 4 {
+                return Err("trying to read beyond end of slice".into());
+            }
+            // targets are relative to the end of the whole instruction
+            let next = code.len() - cur.len() + count * 4;
+            let mut targets = Vec::with_capacity(count);
+            for _ in 0..count {
+                targets.push(branch_target(next, cur.read_i32::<LittleEndian>()?)?);
+            }
+            RawOperand::Switch(targets.into_boxed_slice())
+        },
+        OperandType::InlineMethod |
+        OperandType::InlineField |
+        OperandType::InlineType |
+        OperandType::InlineTok |
+        OperandType::InlineString |
+        OperandType::InlineSig => RawOperand::Token(cur.read_u32::<LittleEndian>()?),
+    };
+
+    Ok((opcode, operand, code.len() - cur.len()))
+}
+
+fn token_row(token: u32) -> Result<u32> {
+    match token & 0x00ff_ffff {
+        0 => Err("Null token in CIL instruction".into()),
+        row => Ok(row - 1)
+    }
+}
+
+fn resolve_method<'db>(token: u32, db: &'db Database<'db>) -> Result<MethodToken<'db>> {
+    let row = token_row(token)?;
+    Ok(match token >> 24 {
+        0x06 => MethodToken::MethodDef(db.get_table::<MethodDef>().get_row(row)?),
+        0x0a => MethodToken::MemberRef(db.get_table::<MemberRef>().get_row(row)?),
+        0x2b => MethodToken::MethodSpec(db.get_table::<MethodSpec>().get_row(row)?),
+        _ => return Err("Token does not reference a method".into())
+    })
+}
+
+fn resolve_field<'db>(token: u32, db: &'db Database<'db>) -> Result<FieldToken<'db>> {
+    let row = token_row(token)?;
+    Ok(match token >> 24 {
+        0x04 => FieldToken::Field(db.get_table::<Field>().get_row(row)?),
+        0x0a => FieldToken::MemberRef(db.get_table::<MemberRef>().get_row(row)?),
+        _ => return Err("Token does not reference a field".into())
+    })
+}
+
+fn resolve_type<'db>(token: u32, db: &'db Database<'db>) -> Result<TypeDefOrRef<'db>> {
+    TypeDefOrRef::from_token(token, db)?.ok_or(crate::DecodeError("Null token in CIL instruction"))
+}
+
+fn resolve_operand<'db>(opcode: Opcode, raw: RawOperand, db: &'db Database<'db>) -> Result<Operand<'db>> {
+    Ok(match raw {
+        RawOperand::None => Operand::None,
+        RawOperand::Int8(v) => Operand::Int8(v),
+        RawOperand::Int32(v) => Operand::Int32(v),
+        RawOperand::Int64(v) => Operand::Int64(v),
+        RawOperand::Float32(v) => Operand::Float32(v),
+        RawOperand::Float64(v) => Operand::Float64(v),
+        RawOperand::Var(v) => Operand::Var(v),
+        RawOperand::BranchTarget(v) => Operand::BranchTarget(v),
+        RawOperand::Switch(v) => Operand::Switch(v),
+        RawOperand::Token(token) => match opcode.operand_type() {
+            OperandType::InlineMethod => Operand::Method(resolve_method(token, db)?),
+            OperandType::InlineField => Operand::Field(resolve_field(token, db)?),
+            OperandType::InlineType => Operand::Type(resolve_type(token, db)?),
+            OperandType::InlineTok => Operand::Token(match token >> 24 {
+                0x01 | 0x02 | 0x1b => Token::Type(resolve_type(token, db)?),
+                0x04 => Token::Field(resolve_field(token, db)?),
+                0x06 | 0x2b => Token::Method(resolve_method(token, db)?),
+                0x0a => {
+                    let member_ref = db.get_table::<MemberRef>().get_row(token_row(token)?)?;
+                    if member_ref.is_field()? {
+                        Token::Field(FieldToken::MemberRef(member_ref))
+                    } else {
+                        Token::Method(MethodToken::MemberRef(member_ref))
+                    }
+                },
+                _ => return Err("Invalid token for ldtoken".into())
+            }),
+            OperandType::InlineString => {
+                if token >> 24 != 0x70 {
+                    return Err("Token does not reference a user string".into());
+                }
+                Operand::String(db.get_user_string(token & 0x00ff_ffff)?)
+            },
+            OperandType::InlineSig => {
+                if token >> 24 != 0x11 {
+                    return Err("Token does not reference a StandAloneSig".into());
+                }
+                Operand::Signature(db.get_table::<StandAloneSig>().get_row(token_row(token)?)?)
+            },
+            _ => unreachable!()
+        }
+    })
+}
+
+// ECMA-335, III.1
+#[derive(Clone, Debug)]
+pub struct Instruction<'db> {
+    m_offset: u32,
+    m_size: u32,
+    m_opcode: Opcode,
+    m_operand: Operand<'db>,
+}
+
+impl<'db> Instruction<'db> {
+    /// The offset of this instruction from the beginning of the method's code.
+    pub fn offset(&self) -> u32 {
+        self.m_offset
+    }
+
+    /// The size of this instruction in bytes, including its operand.
+    pub fn size(&self) -> u32 {
+        self.m_size
+    }
+
+    pub fn opcode(&self) -> Opcode {
+        self.m_opcode
+    }
+
+    pub fn operand(&self) -> &Operand<'db> {
+        &self.m_operand
+    }
+}
+
+/// Iterator over the instructions of a method body. Decoding stops after the first error.
+pub struct Instructions<'db> {
+    m_code: &'db [u8],
+    m_offset: usize,
+    m_db: &'db Database<'db>,
+}
+
+impl<'db> Instructions<'db> {
+    pub(crate) fn new(code: &'db [u8], db: &'db Database<'db>) -> Instructions<'db> {
+        Instructions {
+            m_code: code,
+            m_offset: 0,
+            m_db: db
+        }
+    }
+}
+
+impl<'db> Iterator for Instructions<'db> {
+    type Item = Result<Instruction<'db>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.m_offset >= self.m_code.len() {
+            return None;
+        }
+
+        let offset = self.m_offset;
+        let result = decode_raw(self.m_code, offset).and_then(|(opcode, raw, next)| {
+            self.m_offset = next;
+            Ok(Instruction {
+                m_offset: offset as u32,
+                m_size: (next - offset) as u32,
+                m_opcode: opcode,
+                m_operand: resolve_operand(opcode, raw, self.m_db)?
+            })
+        });
+
+        if result.is_err() {
+            self.m_offset = self.m_code.len();
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_raw, Opcode, OperandType, RawOperand};
+
+    fn decode_all(code: &[u8]) -> Vec<(usize, Opcode, RawOperand)> {
+        let mut result = Vec::new();
+        let mut offset = 0;
+        while offset < code.len() {
+            let (opcode, operand, next) = decode_raw(code, offset).unwrap();
+            result.push((offset, opcode, operand));
+            offset = next;
+        }
+        result
+    }
+
+    #[test]
+    fn test_decode_simple() {
+        // ldarg.0; ldc.i4.s -2; ldc.i4 0x12345678; add; ceq; ret
+        let code = &[0x02, 0x1f, 0xfe, 0x20, 0x78, 0x56, 0x34, 0x12, 0x58, 0xfe, 0x01, 0x2a];
+        assert_eq!(decode_all(code), vec![
+            (0, Opcode::Ldarg0, RawOperand::None),
+            (1, Opcode::LdcI4S, RawOperand::Int8(-2)),
+            (3, Opcode::LdcI4, RawOperand::Int32(0x12345678)),
+            (8, Opcode::Add, RawOperand::None),
+            (9, Opcode::Ceq, RawOperand::None),
+            (11, Opcode::Ret, RawOperand::None),
+        ]);
+        assert_eq!(Opcode::Ceq.value(), 0xfe01);
+        assert_eq!(Opcode::LdcI4S.name(), "ldc.i4.s");
+    }
+
+    #[test]
+    fn test_decode_branches() {
+        // br.s +1; nop; brtrue -7; switch (0, -12); ret
+        let code = &[0x2b, 0x01, 0x00, 0x3a, 0xf9, 0xff, 0xff, 0xff,
+                     0x45, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf4, 0xff, 0xff, 0xff, 0x2a];
+        assert_eq!(decode_all(code), vec![
+            (0, Opcode::BrS, RawOperand::BranchTarget(3)),
+            (2, Opcode::Nop, RawOperand::None),
+            (3, Opcode::Brtrue, RawOperand::BranchTarget(1)),
+            (8, Opcode::Switch, RawOperand::Switch(vec![21, 9].into_boxed_slice())),
+            (21, Opcode::Ret, RawOperand::None),
+        ]);
+        assert!(decode_raw(&[0x2b, 0x80], 0).is_err()); // target before start of method
+    }
+
+    #[test]
+    fn test_decode_tokens() {
+        // call 0x0A000001; ldstr 0x70000005; ldloc 0x0102
+        let code = &[0x28, 0x01, 0x00, 0x00, 0x0a, 0x72, 0x05, 0x00, 0x00, 0x70, 0xfe, 0x0c, 0x02, 0x01];
+        let decoded = decode_all(code);
+        assert_eq!(decoded[0], (0, Opcode::Call, RawOperand::Token(0x0a000001)));
+        assert_eq!(decoded[0].1.operand_type(), OperandType::InlineMethod);
+        assert_eq!(decoded[1], (5, Opcode::Ldstr, RawOperand::Token(0x70000005)));
+        assert_eq!(decoded[2], (10, Opcode::Ldloc, RawOperand::Var(0x0102)));
+        assert!(decode_raw(&[0x24], 0).is_err()); // unused opcode
+        assert!(decode_raw(&[0x28, 0x01, 0x00], 0).is_err()); // truncated operand
+    }
+
+    #[test]
+    fn test_resolve_operands() {
+        use crate::core::builder::{ImageBuilder, coded, CLASS};
+        use crate::{Database, schema};
+        use crate::schema::TypeDefOrRef;
+        use super::{Operand, Token, MethodToken, FieldToken};
+
+        let mut builder = ImageBuilder::new();
+        let hello = builder.user_string(&[0x48, 0x69]);
+        let mut code = vec![0x72];
+        code.extend_from_slice(&(0x7000_0000 | hello).to_le_bytes()); // ldstr "Hi"
+        code.extend_from_slice(&[0xd0, 0x01, 0x00, 0x00, 0x01]); // ldtoken System.Object
+        code.extend_from_slice(&[0xd0, 0x01, 0x00, 0x00, 0x0a]); // ldtoken field MemberRef
+        code.extend_from_slice(&[0xd0, 0x02, 0x00, 0x00, 0x0a]); // ldtoken method MemberRef
+        code.extend_from_slice(&[0x7b, 0x01, 0x00, 0x00, 0x04]); // ldfld
+        code.extend_from_slice(&[0x28, 0x01, 0x00, 0x00, 0x06]); // call
+        code.extend_from_slice(&[0x45, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00]); // switch
+        code.extend_from_slice(&[0x26, 0x2a]); // pop; ret
+        let mut body = vec![(code.len() << 2) as u8 | 0x02]; // tiny header
+        body.extend_from_slice(&code);
+        let rva = builder.data(&body) as u64;
+        // ldc.i4.0; ldtoken of a MemberRef without a signature
+        let broken_rva = builder.data(&[0x1a, 0x16, 0xd0, 0x03, 0x00, 0x00, 0x0a]) as u64;
+
+        builder.module_type();
+        builder.type_def(CLASS, "N", "Program", 0, 1, 1);
+        let object = builder.type_ref("System", "Object");
+        builder.field(0x0001, "count", &[0x06, 0x08]);
+        let name = builder.string("Main") as u64;
+        let signature = builder.blob(&[0x00, 0x00, 0x01]) as u64;
+        builder.row(0x06, &[rva, 0, 0x0016, name, signature, 1]);
+        builder.row(0x06, &[broken_rva, 0, 0x0016, name, signature, 1]);
+        let names = [builder.string("f") as u64, builder.string("M") as u64, builder.string("broken") as u64];
+        let signatures = [builder.blob(&[0x06, 0x08]) as u64, builder.blob(&[0x20, 0x00, 0x01]) as u64, 0];
+        for (&name, &signature) in names.iter().zip(signatures.iter()) {
+            builder.row(0x0a, &[coded(object, 1, 3), name, signature]);
+        }
+
+        let image = builder.build();
+        let db = Database::from_data(&image).unwrap();
+        let methods = db.table::<schema::MethodDef>();
+        let instructions: Vec<_> = methods.get_row(0).unwrap().body().unwrap().unwrap().instructions()
+            .map(|i| i.unwrap()).collect();
+        let offsets: Vec<_> = instructions.iter().map(|i| i.offset()).collect();
+        assert_eq!(offsets, [0, 5, 10, 15, 20, 25, 30, 43, 44]);
+
+        assert!(matches!(instructions[0].operand(), Operand::String(s) if s == "Hi"));
+        assert!(matches!(instructions[1].operand(), Operand::Token(Token::Type(TypeDefOrRef::TypeRef(t)))
+            if t.type_name().unwrap() == "Object"));
+        assert!(matches!(instructions[2].operand(), Operand::Token(Token::Field(FieldToken::MemberRef(m)))
+            if m.name().unwrap() == "f"));
+        assert!(matches!(instructions[3].operand(), Operand::Token(Token::Method(MethodToken::MemberRef(m)))
+            if m.name().unwrap() == "M"));
+        assert!(matches!(instructions[4].operand(), Operand::Field(FieldToken::Field(f)) if f.name().unwrap() == "count"));
+        assert!(matches!(instructions[5].operand(), Operand::Method(MethodToken::MethodDef(m)) if m.name().unwrap() == "Main"));
+        assert!(matches!(instructions[6].operand(), Operand::Switch(targets) if targets[..] == [43, 44]));
+
+        let mut broken = methods.get_row(1).unwrap().body().unwrap().unwrap().instructions();
+        assert!(matches!(broken.next().unwrap().unwrap().operand(), Operand::None));
+        assert!(broken.next().unwrap().is_err());
+        assert!(broken.next().is_none());
+        assert!(db.table::<schema::MemberRef>().get_row(2).unwrap().signature().is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::mem;
 use byteorder::{ReadBytesExt, LittleEndian};
 use crate::Result;
@@ -5,9 +6,12 @@ use crate::{Cache, ResolveToTypeDef};
 use crate::core::db::Database;
 use super::{Type, TypeTag, PrimitiveType, MethodDefSig, ParamKind, bits};
 
-fn read_string<'db>(cursor: &mut &'db [u8]) -> Result<Option<&'db str>> {
+pub(super) fn read_string<'db>(cursor: &mut &'db [u8]) -> Result<Option<&'db str>> {
     let length = super::uncompress_unsigned(cursor)?;
     if length == 0xff { return Ok(None); }
+    if length as usize > cursor.len() {
+        return Err("String length exceeds the end of the blob".into());
+    }
     let (left, mut right) = cursor.split_at(length as usize);
     mem::swap(cursor, &mut right);
     Ok(Some(std::str::from_utf8(left).map_err(|_| crate::DecodeError("Invalid UTF8 in constant value"))?))
@@ -63,6 +67,25 @@ impl<'db> CustomAttributeSig<'db> {
     }
 }
 
+impl<'db> fmt::Display for CustomAttributeSig<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.21.2 (the verbal form, e.g. `{int32(1) property bool 'AllowMultiple' = bool(true)}`)
+        write!(f, "{{")?;
+        let mut first = true;
+        for arg in &self.m_fixed {
+            if !first { write!(f, " ")?; }
+            first = false;
+            write!(f, "{}", arg)?;
+        }
+        for arg in &self.m_named {
+            if !first { write!(f, " ")?; }
+            first = false;
+            write!(f, "{}", arg)?;
+        }
+        write!(f, "}}")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum FixedArg<'db> {
     Elem(Elem<'db>),
@@ -73,7 +96,16 @@ impl<'db> FixedArg<'db> {
     fn parse<'c: 'db>(cur: &mut &'db [u8], db: &'db Database, kind: ElemKind<'c>) -> Result<FixedArg<'db>> {
         Ok(match kind {
             ElemKind::Elem(t) => FixedArg::Elem(t.parse_value(cur)?),
-            ElemKind::Array(t) => FixedArg::Array(unimplemented!()) // TODO
+            ElemKind::Array(t) => {
+                let count = cur.read_u32::<LittleEndian>()?;
+                let mut elems = Vec::new();
+                if count != 0xffff_ffff { // 0xFFFFFFFF indicates a null array, which we treat as empty
+                    for _ in 0..count {
+                        elems.push(t.clone().parse_value(cur)?);
+                    }
+                }
+                FixedArg::Array(elems)
+            }
         })
     }
 }
@@ -84,6 +116,26 @@ pub enum NamedArgName<'db> {
     Property(&'db str)
 }
 
+impl<'db> fmt::Display for FixedArg<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.21.2 (the element type of an empty array is not known and therefore omitted)
+        match self {
+            FixedArg::Elem(elem) => write!(f, "{}", elem),
+            FixedArg::Array(elems) => {
+                if let Some(first) = elems.first() {
+                    first.fmt_type(f)?;
+                }
+                write!(f, "[{}](", elems.len())?;
+                for (i, elem) in elems.iter().enumerate() {
+                    if i > 0 { write!(f, " ")?; }
+                    elem.fmt_value(f)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct NamedArg<'db> {
     pub name: NamedArgName<'db>,
@@ -91,7 +143,7 @@ pub struct NamedArg<'db> {
 }
 
 impl<'db> NamedArg<'db> {
-    fn parse<'c: 'db>(cur: &mut &'db [u8], db: &'db Database, cache: &Cache<'c>) -> Result<NamedArg<'db>> {
+    pub(super) fn parse<'c: 'db>(cur: &mut &'db [u8], db: &'db Database, cache: &Cache<'c>) -> Result<NamedArg<'db>> {
         let is_property = match cur.read_u8()? {
             bits::ARG_FIELD => false,
             bits::ARG_PROPERTY => true,
@@ -112,6 +164,27 @@ impl<'db> NamedArg<'db> {
     }
 }
 
+impl<'db> fmt::Display for NamedArg<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self.name {
+            NamedArgName::Field(name) => { write!(f, "field ")?; name },
+            NamedArgName::Property(name) => { write!(f, "property ")?; name }
+        };
+        match self.value {
+            FixedArg::Elem(ref elem) => elem.fmt_type(f)?,
+            FixedArg::Array(ref elems) => {
+                if let Some(first) = elems.first() {
+                    first.fmt_type(f)?;
+                }
+                write!(f, "[]")?;
+            }
+        }
+        write!(f, " ")?;
+        super::fmt_quoted(name, '\'', f)?;
+        write!(f, " = {}", self.value)
+    }
+}
+
 #[derive(Clone, Debug)]
 enum FieldOrPropType<'db> {
     Primitive(PrimitiveType),
@@ -191,7 +264,7 @@ enum ElemKind<'db> {
 impl<'db> ElemKind<'db> {
     fn from_fixed_arg_type<'c: 'db>(typ: &Type<'db>, cache: &Cache<'c>) -> Result<ElemKind<'db>> {
         Ok(match typ {
-            Type::Array(array) => ElemKind::Array(FieldOrPropType::from_fixed_arg_type(array.elem_type(), cache)?),
+            Type::Array(array) if array.shape().is_none() => ElemKind::Array(FieldOrPropType::from_fixed_arg_type(array.elem_type(), cache)?),
             _ => ElemKind::Elem(FieldOrPropType::from_fixed_arg_type(typ, cache)?)
         })
     }
@@ -216,3 +289,241 @@ pub enum Elem<'db> {
     SystemType(&'db str),
     EnumValue(super::TypeDef<'db>, super::PrimitiveValue)
 }
+
+impl<'db> Elem<'db> {
+    fn fmt_type(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Elem::Primitive(p) => write!(f, "{}", p.type_name()),
+            Elem::String(_) => write!(f, "string"),
+            Elem::SystemType(_) => write!(f, "type"),
+            Elem::EnumValue(_, p) => write!(f, "{}", p.type_name())
+        }
+    }
+
+    // Writes the value without the surrounding type name, as used inside of arrays
+    fn fmt_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Elem::Primitive(p) | Elem::EnumValue(_, p) => p.fmt_value(f),
+            Elem::String(Some(s)) | Elem::SystemType(s) => super::fmt_quoted(s, '\'', f),
+            Elem::String(None) => write!(f, "nullref")
+        }
+    }
+}
+
+impl<'db> fmt::Display for Elem<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.21.2 (enum values are written as their underlying type)
+        self.fmt_type(f)?;
+        write!(f, "(")?;
+        self.fmt_value(f)?;
+        write!(f, ")")
+    }
+}
+
+/// Conversion of a custom attribute into a Rust type, usually implemented with
+/// `#[derive(FromCustomAttribute)]` (requires the `derive` feature).
+pub trait FromCustomAttribute<'db>: Sized {
+    const TYPE_NAMESPACE: &'static str;
+    const TYPE_NAME: &'static str;
+
+    fn from_custom_attribute(sig: &CustomAttributeSig<'db>) -> Result<Self>;
+}
+
+/// Conversion of a single (fixed or named) custom attribute argument into a Rust value.
+pub trait FromAttributeArg<'db>: Sized {
+    fn from_elem(_elem: &Elem<'db>) -> Option<Self> {
+        None
+    }
+
+    fn from_arg(arg: &FixedArg<'db>) -> Option<Self> {
+        match arg {
+            FixedArg::Elem(elem) => Self::from_elem(elem),
+            FixedArg::Array(_) => None
+        }
+    }
+
+    /// The value to use if a named argument is not present, or `None` if the argument is required.
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+macro_rules! impl_from_attribute_arg {
+    ($($ty:ty => $variant:ident),+) => {
+        $(
+            impl<'db> FromAttributeArg<'db> for $ty {
+                fn from_elem(elem: &Elem<'db>) -> Option<Self> {
+                    match elem {
+                        Elem::Primitive(super::PrimitiveValue::$variant(v)) => Some(*v),
+                        Elem::EnumValue(_, super::PrimitiveValue::$variant(v)) => Some(*v),
+                        _ => None
+                    }
+                }
+            }
+        )+
+    }
+}
+
+impl_from_attribute_arg!(bool => Boolean, i8 => Int8, u8 => UInt8, i16 => Int16, u16 => UInt16, i32 => Int32,
+                         u32 => UInt32, i64 => Int64, u64 => UInt64, f32 => Float32, f64 => Float64);
+
+impl<'db> FromAttributeArg<'db> for char {
+    fn from_elem(elem: &Elem<'db>) -> Option<Self> {
+        match elem {
+            Elem::Primitive(super::PrimitiveValue::Char(c)) => std::char::from_u32(*c as u32),
+            _ => None
+        }
+    }
+}
+
+// Strings also match System.Type arguments, which are stored as the type name
+impl<'db> FromAttributeArg<'db> for &'db str {
+    fn from_elem(elem: &Elem<'db>) -> Option<Self> {
+        match elem {
+            Elem::String(Some(s)) | Elem::SystemType(s) => Some(s),
+            _ => None
+        }
+    }
+}
+
+impl<'db> FromAttributeArg<'db> for String {
+    fn from_elem(elem: &Elem<'db>) -> Option<Self> {
+        <&str>::from_elem(elem).map(|s| s.to_owned())
+    }
+}
+
+impl<'db, T: FromAttributeArg<'db>> FromAttributeArg<'db> for Option<T> {
+    fn from_elem(elem: &Elem<'db>) -> Option<Self> {
+        match elem {
+            Elem::String(None) => Some(None),
+            _ => T::from_elem(elem).map(Some)
+        }
+    }
+
+    fn from_arg(arg: &FixedArg<'db>) -> Option<Self> {
+        match arg {
+            FixedArg::Elem(elem) => Self::from_elem(elem),
+            FixedArg::Array(_) => T::from_arg(arg).map(Some)
+        }
+    }
+
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<'db, T: FromAttributeArg<'db>> FromAttributeArg<'db> for Vec<T> {
+    fn from_arg(arg: &FixedArg<'db>) -> Option<Self> {
+        match arg {
+            FixedArg::Array(elems) => elems.iter().map(T::from_elem).collect(),
+            FixedArg::Elem(_) => None
+        }
+    }
+}
+
+impl<'db> CustomAttributeSig<'db> {
+    /// Converts the fixed argument at `index`, returning `None` if there is no such argument
+    /// or if it has an incompatible type.
+    pub fn fixed_arg<T: FromAttributeArg<'db>>(&self, index: usize) -> Option<T> {
+        self.m_fixed.get(index).and_then(T::from_arg)
+    }
+
+    /// Converts the named argument (field or property) with the given name, returning
+    /// `T::missing()` if there is no such argument, or `None` if it has an incompatible type.
+    pub fn named_arg<T: FromAttributeArg<'db>>(&self, name: &str) -> Option<T> {
+        let arg = self.m_named.iter().find(|arg| match arg.name {
+            NamedArgName::Field(n) | NamedArgName::Property(n) => n == name
+        });
+        match arg {
+            Some(arg) => T::from_arg(&arg.value),
+            None => T::missing()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use climeta_derive::FromCustomAttribute;
+    use super::{CustomAttributeSig, FixedArg, NamedArg, NamedArgName, Elem, FromCustomAttribute};
+    use crate::schema::PrimitiveValue;
+
+    #[derive(FromCustomAttribute)]
+    #[climeta(attr = "Windows.Foundation.Metadata.VersionAttribute")]
+    struct Version(u32);
+
+    #[derive(FromCustomAttribute)]
+    #[climeta(attr = "System.AttributeUsageAttribute")]
+    struct AttributeUsage {
+        targets: u32,
+        #[climeta(named)]
+        allow_multiple: Option<bool>,
+        #[climeta(named = "Inherited")]
+        inherit: Option<bool>,
+    }
+
+    #[derive(FromCustomAttribute)]
+    #[climeta(attr = "Example.NamesAttribute")]
+    struct Names<'db>(Option<&'db str>, Vec<String>);
+
+    fn sig<'db>(fixed: Vec<FixedArg<'db>>, named: Vec<NamedArg<'db>>) -> CustomAttributeSig<'db> {
+        CustomAttributeSig { m_fixed: fixed, m_named: named }
+    }
+
+    #[test]
+    fn test_derive_positional() {
+        assert_eq!(Version::TYPE_NAMESPACE, "Windows.Foundation.Metadata");
+        assert_eq!(Version::TYPE_NAME, "VersionAttribute");
+
+        let s = sig(vec![FixedArg::Elem(Elem::Primitive(PrimitiveValue::UInt32(0x0a00_0000)))], vec![]);
+        assert_eq!(Version::from_custom_attribute(&s).unwrap().0, 0x0a00_0000);
+
+        let s = sig(vec![], vec![]);
+        assert_eq!(Version::from_custom_attribute(&s).err().unwrap().to_string(),
+                   "Windows.Foundation.Metadata.VersionAttribute: missing constructor argument 0");
+
+        let s = sig(vec![FixedArg::Elem(Elem::String(Some("1.0")))], vec![]);
+        assert_eq!(Version::from_custom_attribute(&s).err().unwrap().to_string(),
+                   "Windows.Foundation.Metadata.VersionAttribute: constructor argument 0 has an unexpected type");
+    }
+
+    #[test]
+    fn test_derive_named() {
+        let s = sig(vec![FixedArg::Elem(Elem::Primitive(PrimitiveValue::UInt32(4)))], vec![
+            NamedArg { name: NamedArgName::Property("AllowMultiple"), value: FixedArg::Elem(Elem::Primitive(PrimitiveValue::Boolean(true))) }
+        ]);
+        let usage = AttributeUsage::from_custom_attribute(&s).unwrap();
+        assert_eq!(usage.targets, 4);
+        assert_eq!(usage.allow_multiple, Some(true));
+        assert_eq!(usage.inherit, None);
+
+        let s = sig(vec![FixedArg::Elem(Elem::Primitive(PrimitiveValue::UInt32(4)))], vec![
+            NamedArg { name: NamedArgName::Property("Inherited"), value: FixedArg::Elem(Elem::String(Some("yes"))) }
+        ]);
+        assert_eq!(AttributeUsage::from_custom_attribute(&s).err().unwrap().to_string(),
+                   "System.AttributeUsageAttribute: named argument `Inherited` is missing or has an unexpected type");
+    }
+
+    #[test]
+    fn test_derive_strings_and_arrays() {
+        let s = sig(vec![
+            FixedArg::Elem(Elem::String(None)),
+            FixedArg::Array(vec![Elem::String(Some("a")), Elem::String(Some("b"))])
+        ], vec![]);
+        let names = Names::from_custom_attribute(&s).unwrap();
+        assert_eq!(names.0, None);
+        assert_eq!(names.1, vec!["a".to_owned(), "b".to_owned()]);
+    }
+
+    #[test]
+    fn test_display() {
+        let s = sig(vec![
+            FixedArg::Elem(Elem::Primitive(PrimitiveValue::Int32(1))),
+            FixedArg::Elem(Elem::String(Some("it's"))),
+            FixedArg::Array(vec![Elem::Primitive(PrimitiveValue::UInt8(1)), Elem::Primitive(PrimitiveValue::UInt8(2))])
+        ], vec![
+            NamedArg { name: NamedArgName::Property("AllowMultiple"), value: FixedArg::Elem(Elem::Primitive(PrimitiveValue::Boolean(true))) }
+        ]);
+        assert_eq!(s.to_string(), "{int32(1) string('it\\'s') unsigned int8[2](1 2) property bool 'AllowMultiple' = bool(true)}");
+        assert_eq!(sig(vec![FixedArg::Elem(Elem::String(None))], vec![]).to_string(), "{string(nullref)}");
+    }
+}
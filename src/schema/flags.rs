@@ -6,6 +6,7 @@ mod method_attributes;
 mod method_impl_attributes;
 mod method_semantics_attributes;
 mod param_attributes;
+mod pinvoke_attributes;
 mod property_attributes;
 mod type_attributes;
 
@@ -17,6 +18,7 @@ pub use method_attributes::*;
 pub use method_impl_attributes::*;
 pub use method_semantics_attributes::*;
 pub use param_attributes::*;
+pub use pinvoke_attributes::*;
 pub use property_attributes::*;
 pub use type_attributes::*;
 
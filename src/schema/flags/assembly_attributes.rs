@@ -1,14 +1,62 @@
 use crate::core::BitView;
 
-pub struct AssemblyAttributes(pub(crate) u16);
+// ECMA-335, II.23.1.2
+pub struct AssemblyAttributes(pub(crate) u32);
 
 #[allow(non_upper_case_globals)]
 pub(super) mod bits {
+    pub const PublicKey_bit: usize = 0;
+    pub const Retargetable_bit: usize = 8;
+    pub const ContentType_mask: u32 = 0x00000E00;
     pub const WindowsRuntime_bit: usize = 9;
+    pub const DisableJITcompileOptimizer_bit: usize = 14;
+    pub const EnableJITcompileTracking_bit: usize = 15;
+}
+
+#[repr(u32)]
+#[derive(FromPrimitive, ToPrimitive)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AssemblyContentType {
+    Default = 0x00000000,
+    WindowsRuntime = 0x00000200,
 }
 
 impl AssemblyAttributes {
+    /// The assembly reference holds the full (unhashed) public key.
+    pub fn public_key(&self) -> bool {
+        self.0.get_bit(bits::PublicKey_bit)
+    }
+
+    pub fn retargetable(&self) -> bool {
+        self.0.get_bit(bits::Retargetable_bit)
+    }
+
+    pub fn content_type(&self) -> Option<AssemblyContentType> {
+        num_traits::FromPrimitive::from_u32(self.0 & bits::ContentType_mask)
+    }
+
     pub fn windows_runtime(&self) -> bool {
         self.0.get_bit(bits::WindowsRuntime_bit)
     }
-}
\ No newline at end of file
+
+    pub fn disable_jit_compile_optimizer(&self) -> bool {
+        self.0.get_bit(bits::DisableJITcompileOptimizer_bit)
+    }
+
+    pub fn enable_jit_compile_tracking(&self) -> bool {
+        self.0.get_bit(bits::EnableJITcompileTracking_bit)
+    }
+}
+
+// ECMA-335, II.23.1.1 (the SHA-2 values are used by newer compilers)
+#[repr(u32)]
+#[derive(FromPrimitive, ToPrimitive)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AssemblyHashAlgorithm {
+    None = 0x0000,
+    MD5 = 0x8003,
+    SHA1 = 0x8004,
+    SHA256 = 0x800C,
+    SHA384 = 0x800D,
+    SHA512 = 0x800E,
+}
@@ -0,0 +1,112 @@
+use crate::core::BitView;
+
+// ECMA-335, II.23.1.8
+pub struct PInvokeAttributes(pub(crate) u16);
+
+#[allow(non_upper_case_globals)]
+pub(super) mod bits {
+    pub const NoMangle_bit: usize = 0; // PInvoke is to use the member name as specified
+    pub const CharSet_mask: u16 = 0x0006;
+    pub const BestFit_mask: u16 = 0x0030; // not in ECMA-335
+    pub const SupportsLastError_bit: usize = 6;
+    pub const CallConv_mask: u16 = 0x0700;
+    pub const ThrowOnUnmappableChar_mask: u16 = 0x3000; // not in ECMA-335
+}
+
+#[repr(u16)]
+#[derive(FromPrimitive, ToPrimitive)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CharSet {
+    NotSpec = 0x0000,
+    Ansi = 0x0002,
+    Unicode = 0x0004,
+    Auto = 0x0006,
+}
+
+#[repr(u16)]
+#[derive(FromPrimitive, ToPrimitive)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PInvokeCallConv {
+    Winapi = 0x0100, // the platform default
+    Cdecl = 0x0200,
+    Stdcall = 0x0300,
+    Thiscall = 0x0400,
+    Fastcall = 0x0500,
+}
+
+impl PInvokeAttributes {
+    pub fn no_mangle(&self) -> bool {
+        self.0.get_bit(bits::NoMangle_bit)
+    }
+
+    pub fn char_set(&self) -> CharSet {
+        self.0.get_enum::<CharSet>(bits::CharSet_mask)
+    }
+
+    /// `Some(true)` or `Some(false)` if best-fit mapping of characters was explicitly enabled or disabled.
+    pub fn best_fit(&self) -> Option<bool> {
+        match self.0 & bits::BestFit_mask {
+            0x0010 => Some(true),
+            0x0020 => Some(false),
+            _ => None
+        }
+    }
+
+    pub fn supports_last_error(&self) -> bool {
+        self.0.get_bit(bits::SupportsLastError_bit)
+    }
+
+    pub fn call_conv(&self) -> Option<PInvokeCallConv> {
+        num_traits::FromPrimitive::from_u16(self.0 & bits::CallConv_mask)
+    }
+
+    /// `Some(true)` or `Some(false)` if throwing on unmappable characters was explicitly enabled or disabled.
+    pub fn throw_on_unmappable_char(&self) -> Option<bool> {
+        match self.0 & bits::ThrowOnUnmappableChar_mask {
+            0x1000 => Some(true),
+            0x2000 => Some(false),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PInvokeAttributes, CharSet, PInvokeCallConv};
+
+    #[test]
+    fn test_char_set() {
+        assert_eq!(PInvokeAttributes(0x0000).char_set(), CharSet::NotSpec);
+        assert_eq!(PInvokeAttributes(0x0002).char_set(), CharSet::Ansi);
+        assert_eq!(PInvokeAttributes(0x0004).char_set(), CharSet::Unicode);
+        assert_eq!(PInvokeAttributes(0x0006).char_set(), CharSet::Auto);
+        // the other flags do not affect the character set
+        assert_eq!(PInvokeAttributes(0x3775).char_set(), CharSet::Unicode);
+    }
+
+    #[test]
+    fn test_call_conv() {
+        assert_eq!(PInvokeAttributes(0x0000).call_conv(), None);
+        assert_eq!(PInvokeAttributes(0x0100).call_conv(), Some(PInvokeCallConv::Winapi));
+        assert_eq!(PInvokeAttributes(0x0200).call_conv(), Some(PInvokeCallConv::Cdecl));
+        assert_eq!(PInvokeAttributes(0x0300).call_conv(), Some(PInvokeCallConv::Stdcall));
+        assert_eq!(PInvokeAttributes(0x0400).call_conv(), Some(PInvokeCallConv::Thiscall));
+        assert_eq!(PInvokeAttributes(0x0500).call_conv(), Some(PInvokeCallConv::Fastcall));
+        assert_eq!(PInvokeAttributes(0x0600).call_conv(), None);
+        assert_eq!(PInvokeAttributes(0x3477).call_conv(), Some(PInvokeCallConv::Thiscall));
+    }
+
+    #[test]
+    fn test_flags() {
+        let flags = PInvokeAttributes(0x0041);
+        assert!(flags.no_mangle());
+        assert!(flags.supports_last_error());
+        assert_eq!(flags.best_fit(), None);
+        assert_eq!(flags.throw_on_unmappable_char(), None);
+        let flags = PInvokeAttributes(0x2010);
+        assert!(!flags.no_mangle());
+        assert!(!flags.supports_last_error());
+        assert_eq!(flags.best_fit(), Some(true));
+        assert_eq!(flags.throw_on_unmappable_char(), Some(false));
+    }
+}
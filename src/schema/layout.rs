@@ -0,0 +1,237 @@
+use crate::{Result, Cache, ResolveToTypeDef};
+use super::{TypeDef, Field, TypeDefOrRef, TypeCategory, Type, TypeTag};
+use super::flags::TypeLayout;
+
+/// The memory layout of a value type with sequential or explicit layout, as computed
+/// by `TypeDef::layout()` for a given pointer size (ECMA-335, II.10.1.2 and II.10.7).
+#[derive(Clone)]
+pub struct ValueTypeLayout<'db> {
+    m_size: u32,
+    m_alignment: u32,
+    m_fields: Vec<FieldOffset<'db>>,
+}
+
+#[derive(Clone)]
+pub struct FieldOffset<'db> {
+    pub field: Field<'db>,
+    pub offset: u32,
+    pub size: u32,
+}
+
+impl<'db> ValueTypeLayout<'db> {
+    pub fn size(&self) -> u32 {
+        self.m_size
+    }
+
+    pub fn alignment(&self) -> u32 {
+        self.m_alignment
+    }
+
+    /// The instance fields with their offsets, in declaration order.
+    pub fn fields(&self) -> &[FieldOffset<'db>] {
+        &self.m_fields
+    }
+
+    pub(crate) fn compute<'c: 'db>(typ: &TypeDef<'db>, cache: &Cache<'c>, pointer_size: u32) -> Result<ValueTypeLayout<'db>> {
+        if pointer_size != 4 && pointer_size != 8 {
+            return Err("Pointer size must be 4 or 8".into());
+        }
+        LayoutEngine { cache, pointer_size, stack: Vec::new() }.compute(typ)
+    }
+}
+
+struct LayoutEngine<'a, 'c, 'db> {
+    cache: &'a Cache<'c>,
+    pointer_size: u32,
+    stack: Vec<TypeDef<'db>>, // to detect cyclic value types
+}
+
+impl<'a, 'c: 'db, 'db> LayoutEngine<'a, 'c, 'db> {
+    fn compute(&mut self, typ: &TypeDef<'db>) -> Result<ValueTypeLayout<'db>> {
+        if typ.type_category()? != TypeCategory::Struct {
+            return Err("Layout can only be computed for value types".into());
+        }
+        if self.stack.contains(typ) {
+            return Err("Value type contains itself".into());
+        }
+        let explicit = match typ.flags()?.layout() {
+            TypeLayout::SequentialLayout => false,
+            TypeLayout::ExplicitLayout => true,
+            TypeLayout::AutoLayout => return Err("Value types with auto layout have no defined layout".into())
+        };
+        let (packing, class_size) = match typ.class_layout()? {
+            Some(layout) => (layout.packing_size()? as u32, layout.class_size()?),
+            None => (0, 0)
+        };
+
+        self.stack.push(typ.clone());
+        let mut fields = Vec::new();
+        let mut alignment = 1;
+        let mut end = 0;
+        for field in typ.field_list()? {
+            let flags = field.flags()?;
+            if flags.static_() || flags.literal() {
+                continue;
+            }
+            let (size, mut field_alignment) = self.size_and_alignment(field.signature()?.type_())?;
+            if packing != 0 {
+                field_alignment = field_alignment.min(packing);
+            }
+            let offset = if explicit {
+                field.explicit_offset()?.ok_or(crate::DecodeError("Field of explicit layout type has no FieldLayout offset"))?
+            } else {
+                align_up(end, field_alignment)?
+            };
+            alignment = alignment.max(field_alignment);
+            end = end.max(offset.checked_add(size).ok_or(crate::DecodeError("Value type layout overflows"))?);
+            fields.push(FieldOffset { field, offset, size });
+        }
+        self.stack.pop();
+
+        let size = align_up(end, alignment)?.max(class_size).max(1); // empty structs have size 1
+        Ok(ValueTypeLayout {
+            m_size: size,
+            m_alignment: alignment,
+            m_fields: fields
+        })
+    }
+
+    fn size_and_alignment(&mut self, typ: &Type<'db>) -> Result<(u32, u32)> {
+        Ok(match typ {
+            Type::Primitive(p) => {
+                let size = p.size().unwrap_or(self.pointer_size);
+                (size, size)
+            },
+            Type::Ref(TypeTag::ValueType, t, None) => {
+                let resolved = match t {
+                    TypeDefOrRef::TypeDef(d) => d.clone(),
+                    TypeDefOrRef::TypeRef(_) => t.resolve(self.cache).ok_or(crate::DecodeError("Unresolvable value type in layout"))?,
+                    TypeDefOrRef::TypeSpec(_) => return Err("Generic value types are not supported in layout".into())
+                };
+                if resolved.is_enum() {
+                    let size = resolved.enum_get_underlying_type()?.size().unwrap_or(self.pointer_size);
+                    (size, size)
+                } else {
+                    let layout = self.compute(&resolved)?;
+                    (layout.m_size, layout.m_alignment)
+                }
+            },
+            Type::Ref(TypeTag::ValueType, _, Some(_)) => return Err("Generic value types are not supported in layout".into()),
+            Type::GenericVar(..) => return Err("Generic variables are not supported in layout".into()),
+            // object references, arrays and (function) pointers
+            Type::Ref(TypeTag::Class, ..) | Type::Object | Type::String | Type::Array(_) |
+            Type::Ptr(..) | Type::FnPtr(_) => (self.pointer_size, self.pointer_size),
+        })
+    }
+}
+
+fn align_up(offset: u32, alignment: u32) -> Result<u32> {
+    match offset % alignment {
+        0 => Ok(offset),
+        rem => offset.checked_add(alignment - rem).ok_or(crate::DecodeError("Value type layout overflows"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cache, Database};
+    use crate::core::builder::{ImageBuilder, coded, CLASS};
+
+    const SEQUENTIAL: u64 = 0x0109; // public, sealed, sequential layout
+    const EXPLICIT: u64 = 0x0111; // public, sealed, explicit layout
+    const AUTO: u64 = 0x0101; // public, sealed, auto layout
+
+    fn offsets(layout: &super::ValueTypeLayout) -> Vec<(u32, u32)> {
+        layout.fields().iter().map(|f| (f.offset, f.size)).collect()
+    }
+
+    #[test]
+    fn test_align_up() {
+        assert_eq!(super::align_up(0, 4).unwrap(), 0);
+        assert_eq!(super::align_up(1, 4).unwrap(), 4);
+        assert_eq!(super::align_up(8, 8).unwrap(), 8);
+        assert_eq!(super::align_up(9, 1).unwrap(), 9);
+        assert_eq!(super::align_up(u32::MAX, 1).unwrap(), u32::MAX);
+        assert!(super::align_up(u32::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn test_layout() {
+        let mut builder = ImageBuilder::new();
+        let value_type = coded(builder.type_ref("System", "ValueType"), 1, 2);
+        builder.module_type();
+        let seq = builder.type_def(SEQUENTIAL, "N", "Seq", value_type, 1, 1);
+        let packed = builder.type_def(SEQUENTIAL, "N", "Packed", value_type, 4, 1);
+        let sized = builder.type_def(SEQUENTIAL, "N", "Sized", value_type, 6, 1);
+        builder.type_def(EXPLICIT, "N", "Explicit", value_type, 7, 1);
+        builder.type_def(SEQUENTIAL, "N", "Outer", value_type, 11, 1);
+        let cycle = builder.type_def(SEQUENTIAL, "N", "Cycle", value_type, 14, 1);
+        builder.type_def(AUTO, "N", "Auto", value_type, 15, 1);
+        builder.type_def(CLASS, "N", "Class", 0, 16, 1);
+        builder.type_def(EXPLICIT, "N", "Overflow", value_type, 16, 1);
+        builder.type_def(EXPLICIT, "N", "Unaligned", value_type, 17, 1);
+
+        builder.field(0x0006, "a", &[0x06, 0x04]); // int8
+        builder.field(0x0006, "b", &[0x06, 0x08]); // int32
+        builder.field(0x0006, "c", &[0x06, 0x06]); // int16
+        builder.field(0x0006, "a", &[0x06, 0x04]);
+        builder.field(0x0006, "b", &[0x06, 0x08]);
+        builder.field(0x0006, "a", &[0x06, 0x08]);
+        let explicit_a = builder.field(0x0006, "a", &[0x06, 0x08]);
+        let explicit_b = builder.field(0x0006, "b", &[0x06, 0x0a]); // int64, overlapping a
+        let explicit_c = builder.field(0x0006, "c", &[0x06, 0x04]);
+        builder.field(0x0016, "s", &[0x06, 0x0a]); // static
+        builder.field(0x0006, "a", &[0x06, 0x04]);
+        builder.field(0x0006, "inner", &[0x06, 0x11, coded(seq, 0, 2) as u8]);
+        builder.field(0x0006, "ptr", &[0x06, 0x18]); // native int
+        builder.field(0x0006, "self", &[0x06, 0x11, coded(cycle, 0, 2) as u8]);
+        builder.field(0x0006, "a", &[0x06, 0x08]);
+        let overflow = builder.field(0x0006, "a", &[0x06, 0x08]);
+        let unaligned_a = builder.field(0x0006, "a", &[0x06, 0x06]);
+        let unaligned_b = builder.field(0x0006, "b", &[0x06, 0x04]);
+
+        builder.row(0x0f, &[1, 0, packed as u64]);
+        builder.row(0x0f, &[0, 16, sized as u64]);
+        builder.row(0x10, &[0, explicit_a as u64]);
+        builder.row(0x10, &[0, explicit_b as u64]);
+        builder.row(0x10, &[8, explicit_c as u64]);
+        builder.row(0x10, &[u32::MAX as u64, overflow as u64]);
+        builder.row(0x10, &[0, unaligned_a as u64]);
+        builder.row(0x10, &[0xffff_fffe, unaligned_b as u64]);
+
+        let image = builder.build();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+        let layout = |name: &str, pointer_size| cache.find("N", name).unwrap().layout(&cache, pointer_size);
+
+        let seq = layout("Seq", 8).unwrap();
+        assert_eq!(offsets(&seq), [(0, 1), (4, 4), (8, 2)]);
+        assert_eq!((seq.size(), seq.alignment()), (12, 4));
+
+        let packed = layout("Packed", 8).unwrap();
+        assert_eq!(offsets(&packed), [(0, 1), (1, 4)]);
+        assert_eq!((packed.size(), packed.alignment()), (5, 1));
+
+        let sized = layout("Sized", 8).unwrap();
+        assert_eq!((sized.size(), sized.alignment()), (16, 4));
+
+        let explicit = layout("Explicit", 8).unwrap();
+        assert_eq!(offsets(&explicit), [(0, 4), (0, 8), (8, 1)]);
+        assert_eq!((explicit.size(), explicit.alignment()), (16, 8));
+
+        let outer = layout("Outer", 8).unwrap();
+        assert_eq!(offsets(&outer), [(0, 1), (4, 12), (16, 8)]);
+        assert_eq!((outer.size(), outer.alignment()), (24, 8));
+        let outer = layout("Outer", 4).unwrap();
+        assert_eq!(offsets(&outer), [(0, 1), (4, 12), (16, 4)]);
+        assert_eq!((outer.size(), outer.alignment()), (20, 4));
+
+        assert!(layout("Cycle", 8).is_err());
+        assert!(layout("Auto", 8).is_err());
+        assert!(layout("Class", 8).is_err());
+        assert!(layout("Seq", 2).is_err());
+        // the end of the last field, or the size rounded up to the alignment, exceeds u32::MAX
+        assert!(layout("Overflow", 8).is_err());
+        assert!(layout("Unaligned", 8).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use std::mem;
+use byteorder::ReadBytesExt;
+use crate::Result;
+use super::uncompress_unsigned;
+
+// ECMA-335, II.23.4 (including the values used by newer runtimes, see CorNativeType in CoreCLR)
+#[repr(u8)]
+#[derive(FromPrimitive, ToPrimitive)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NativeType {
+    Boolean = 0x02,
+    I1 = 0x03,
+    U1 = 0x04,
+    I2 = 0x05,
+    U2 = 0x06,
+    I4 = 0x07,
+    U4 = 0x08,
+    I8 = 0x09,
+    U8 = 0x0a,
+    R4 = 0x0b,
+    R8 = 0x0c,
+    Currency = 0x0f,
+    BStr = 0x13,
+    LPStr = 0x14,
+    LPWStr = 0x15,
+    LPTStr = 0x16,
+    FixedSysString = 0x17,
+    IUnknown = 0x19,
+    IDispatch = 0x1a,
+    Struct = 0x1b,
+    Interface = 0x1c,
+    SafeArray = 0x1d,
+    FixedArray = 0x1e,
+    Int = 0x1f,
+    UInt = 0x20,
+    ByValStr = 0x22,
+    AnsiBStr = 0x23,
+    TBStr = 0x24,
+    VariantBool = 0x25,
+    Func = 0x26,
+    AsAny = 0x28,
+    Array = 0x2a,
+    LPStruct = 0x2b,
+    CustomMarshaler = 0x2c,
+    Error = 0x2d,
+    IInspectable = 0x2e,
+    HString = 0x2f,
+    LPUTF8Str = 0x30,
+}
+
+#[allow(non_upper_case_globals)]
+mod bits {
+    pub const NATIVE_TYPE_MAX: u8 = 0x50; // no element type
+    pub const SizeParamIndexSpecified_bit: u32 = 0x0001;
+}
+
+/// A parsed marshalling descriptor of a field or parameter, as stored in the FieldMarshal table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MarshalSpec<'db> {
+    /// A native type without any further information, e.g. `LPWStr` or `I4`.
+    Intrinsic(NativeType),
+    /// A C-style array whose size is given by a parameter and/or a constant:
+    /// the number of elements is `num_elements` plus the value of the parameter
+    /// at `size_param_index`, if present.
+    Array {
+        element_type: Option<NativeType>,
+        size_param_index: Option<u32>,
+        num_elements: Option<u32>,
+    },
+    /// An array embedded in a structure (`ByValArray`).
+    FixedArray {
+        num_elements: Option<u32>,
+        element_type: Option<NativeType>,
+    },
+    /// A character array embedded in a structure (`ByValTStr`).
+    FixedSysString {
+        size: u32,
+    },
+    /// A COM `SAFEARRAY` with the given `VARTYPE` of its elements.
+    SafeArray {
+        element_type: Option<u32>,
+        user_defined_type: Option<&'db str>,
+    },
+    /// A COM interface pointer (`Interface`, `IUnknown` or `IDispatch`), optionally
+    /// with the index of the parameter that holds the IID.
+    Interface {
+        kind: NativeType,
+        iid_param_index: Option<u32>,
+    },
+    CustomMarshaler {
+        guid: &'db str,
+        native_type_name: &'db str,
+        marshaler_type_name: &'db str,
+        cookie: &'db str,
+    },
+}
+
+fn read_string<'db>(cursor: &mut &'db [u8]) -> Result<&'db str> {
+    let length = uncompress_unsigned(cursor)? as usize;
+    if length > cursor.len() {
+        return Err("String in marshalling descriptor exceeds blob".into());
+    }
+    let (left, mut right) = cursor.split_at(length);
+    mem::swap(cursor, &mut right);
+    std::str::from_utf8(left).map_err(|_| crate::DecodeError("Invalid UTF8 in marshalling descriptor"))
+}
+
+fn read_optional(cursor: &mut &[u8]) -> Result<Option<u32>> {
+    if cursor.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(uncompress_unsigned(cursor)?))
+    }
+}
+
+fn native_type(value: u8) -> Result<NativeType> {
+    num_traits::FromPrimitive::from_u8(value).ok_or(crate::DecodeError("Invalid native type in marshalling descriptor"))
+}
+
+fn read_element_type(cursor: &mut &[u8]) -> Result<Option<NativeType>> {
+    match cursor.first() {
+        None | Some(&bits::NATIVE_TYPE_MAX) => {
+            *cursor = cursor.get(1..).unwrap_or_default();
+            Ok(None)
+        },
+        Some(_) => Ok(Some(native_type(cursor.read_u8()?)?))
+    }
+}
+
+impl<'db> MarshalSpec<'db> {
+    pub(crate) fn parse(cur: &mut &'db [u8]) -> Result<MarshalSpec<'db>> {
+        let kind = native_type(cur.read_u8()?)?;
+        Ok(match kind {
+            NativeType::Array => {
+                let element_type = read_element_type(cur)?;
+                let param_num = read_optional(cur)?;
+                let num_elements = read_optional(cur)?;
+                // older compilers omit the flags, in which case a parameter number is always valid
+                let size_param_index = match read_optional(cur)? {
+                    Some(flags) if flags & bits::SizeParamIndexSpecified_bit == 0 => None,
+                    _ => param_num
+                };
+                MarshalSpec::Array { element_type, size_param_index, num_elements }
+            },
+            NativeType::FixedArray => {
+                let num_elements = read_optional(cur)?;
+                MarshalSpec::FixedArray { num_elements, element_type: read_element_type(cur)? }
+            },
+            NativeType::FixedSysString => MarshalSpec::FixedSysString { size: uncompress_unsigned(cur)? },
+            NativeType::SafeArray => {
+                let element_type = read_optional(cur)?;
+                let user_defined_type = if cur.is_empty() { None } else { Some(read_string(cur)?) };
+                MarshalSpec::SafeArray { element_type, user_defined_type }
+            },
+            NativeType::Interface | NativeType::IUnknown | NativeType::IDispatch => {
+                MarshalSpec::Interface { kind, iid_param_index: read_optional(cur)? }
+            },
+            NativeType::CustomMarshaler => MarshalSpec::CustomMarshaler {
+                guid: read_string(cur)?,
+                native_type_name: read_string(cur)?,
+                marshaler_type_name: read_string(cur)?,
+                cookie: read_string(cur)?
+            },
+            other => MarshalSpec::Intrinsic(other)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MarshalSpec, NativeType};
+
+    fn parse(mut data: &[u8]) -> crate::Result<MarshalSpec<'_>> {
+        MarshalSpec::parse(&mut data)
+    }
+
+    #[test]
+    fn test_marshal_spec() {
+        assert_eq!(parse(&[0x15]).unwrap(), MarshalSpec::Intrinsic(NativeType::LPWStr));
+        assert_eq!(parse(&[0x2a, 0x14, 0x02, 0x00, 0x01]).unwrap(), MarshalSpec::Array {
+            element_type: Some(NativeType::LPStr), size_param_index: Some(2), num_elements: Some(0)
+        });
+        assert_eq!(parse(&[0x2a, 0x50, 0x00, 0x10, 0x00]).unwrap(), MarshalSpec::Array {
+            element_type: None, size_param_index: None, num_elements: Some(16)
+        });
+        assert_eq!(parse(&[0x2a, 0x07, 0x01]).unwrap(), MarshalSpec::Array {
+            element_type: Some(NativeType::I4), size_param_index: Some(1), num_elements: None
+        });
+        assert_eq!(parse(&[0x1e, 0x08, 0x04]).unwrap(), MarshalSpec::FixedArray {
+            num_elements: Some(8), element_type: Some(NativeType::U1)
+        });
+        assert_eq!(parse(&[0x17, 0x20]).unwrap(), MarshalSpec::FixedSysString { size: 32 });
+        assert_eq!(parse(&[0x1c]).unwrap(), MarshalSpec::Interface { kind: NativeType::Interface, iid_param_index: None });
+        assert_eq!(parse(&[0x2c, 0x00, 0x00, 0x03, b'M', b'y', b'M', 0x01, b'x']).unwrap(), MarshalSpec::CustomMarshaler {
+            guid: "", native_type_name: "", marshaler_type_name: "MyM", cookie: "x"
+        });
+        assert!(parse(&[0x01]).is_err());
+        assert!(parse(&[0x2c, 0x05, b'a']).is_err());
+    }
+
+    #[test]
+    fn test_field_marshal() {
+        use crate::core::builder::{ImageBuilder, coded, CLASS};
+        use crate::{Cache, Database, schema};
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        builder.type_def(CLASS, "N", "Native", 0, 1, 1);
+        let name = builder.field(0x0006, "name", &[0x06, 0x0e]);
+        let broken = builder.field(0x0006, "broken", &[0x06, 0x0e]);
+        let lpwstr = builder.blob(&[0x15]) as u64;
+        // FieldMarshal rows are sorted by parent, so the one with a NULL parent comes first
+        builder.row(0x0d, &[0, lpwstr]);
+        builder.row(0x0d, &[coded(name, 0, 1), lpwstr]);
+        builder.row(0x0d, &[coded(broken, 0, 1), 0]);
+
+        let image = builder.build();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+        let fields: Vec<_> = cache.find("N", "Native").unwrap().field_list().unwrap().collect();
+        assert_eq!(fields[0].marshal().unwrap(), Some(MarshalSpec::Intrinsic(NativeType::LPWStr)));
+        assert!(fields[1].marshal().is_err());
+
+        let db = Database::from_data(&image).unwrap();
+        assert!(db.table::<schema::FieldMarshal>().get_row(0).unwrap().parent().is_err());
+    }
+}
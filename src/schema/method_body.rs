@@ -0,0 +1,346 @@
+use byteorder::{ReadBytesExt, LittleEndian};
+
+use crate::Result;
+use crate::core::db::Database;
+use super::{TypeDefOrRef, StandAloneSig, Instructions};
+
+#[allow(non_upper_case_globals, dead_code)]
+mod bits {
+    pub const CorILMethod_FormatMask: u8 = 0x03;
+    pub const CorILMethod_TinyFormat: u8 = 0x02; // II.25.4.2
+    pub const CorILMethod_FatFormat: u8 = 0x03; // II.25.4.3
+    pub const CorILMethod_MoreSects: u16 = 0x08;
+    pub const CorILMethod_InitLocals: u16 = 0x10;
+
+    pub const CorILMethod_Sect_EHTable: u8 = 0x01; // II.25.4.5
+    pub const CorILMethod_Sect_OptILTable: u8 = 0x02;
+    pub const CorILMethod_Sect_FatFormat: u8 = 0x40;
+    pub const CorILMethod_Sect_MoreSects: u8 = 0x80;
+
+    pub const COR_ILEXCEPTION_CLAUSE_EXCEPTION: u32 = 0x0000; // II.25.4.6
+    pub const COR_ILEXCEPTION_CLAUSE_FILTER: u32 = 0x0001;
+    pub const COR_ILEXCEPTION_CLAUSE_FINALLY: u32 = 0x0002;
+    pub const COR_ILEXCEPTION_CLAUSE_FAULT: u32 = 0x0004;
+}
+
+struct Header {
+    flags: u16,
+    max_stack: u16,
+    code_size: u32,
+    local_var_sig_token: u32,
+}
+
+impl Header {
+    fn parse(cur: &mut &[u8]) -> Result<Header> {
+        let first = cur.read_u8()?;
+        match first & bits::CorILMethod_FormatMask {
+            bits::CorILMethod_TinyFormat => {
+                Ok(Header {
+                    flags: 0,
+                    max_stack: 8,
+                    code_size: (first >> 2) as u32,
+                    local_var_sig_token: 0
+                })
+            },
+            bits::CorILMethod_FatFormat => {
+                let flags_and_size = ((cur.read_u8()? as u16) << 8) | first as u16;
+                let header_size = (flags_and_size >> 12) as usize * 4;
+                let max_stack = cur.read_u16::<LittleEndian>()?;
+                let code_size = cur.read_u32::<LittleEndian>()?;
+                let local_var_sig_token = cur.read_u32::<LittleEndian>()?;
+                if header_size < 12 {
+                    return Err("Invalid fat method header size".into());
+                }
+                // skip any additional header bytes that we don't know about
+                if cur.len() < header_size - 12 {
+                    return Err("trying to read beyond end of slice".into());
+                }
+                *cur = &cur[header_size - 12..];
+                Ok(Header {
+                    flags: flags_and_size & 0x0fff,
+                    max_stack,
+                    code_size,
+                    local_var_sig_token
+                })
+            },
+            _ => Err("Invalid method header format".into())
+        }
+    }
+}
+
+// Exception handling clause as stored in the file, before resolving the class token
+struct RawClause {
+    flags: u32,
+    try_offset: u32,
+    try_length: u32,
+    handler_offset: u32,
+    handler_length: u32,
+    class_token_or_filter_offset: u32,
+}
+
+// ECMA-335, II.25.4.5
+fn parse_sections(cur: &mut &[u8]) -> Result<Vec<RawClause>> {
+    let mut clauses = Vec::new();
+
+    loop {
+        let kind = cur.read_u8()?;
+        let is_fat = kind & bits::CorILMethod_Sect_FatFormat != 0;
+        let data_size = if is_fat {
+            let low = cur.read_u16::<LittleEndian>()? as u32;
+            let high = cur.read_u8()? as u32;
+            (high << 16) | low
+        } else {
+            let size = cur.read_u8()? as u32;
+            cur.read_u16::<LittleEndian>()?; // reserved
+            size
+        };
+        let clause_size = if is_fat { 24 } else { 12 };
+        if data_size < 4 || (data_size - 4) % clause_size != 0 {
+            return Err("Invalid method data section size".into());
+        }
+        let count = (data_size - 4) / clause_size;
+
+        if kind & bits::CorILMethod_Sect_EHTable != 0 {
+            for _ in 0..count {
+                clauses.push(if is_fat {
+                    RawClause {
+                        flags: cur.read_u32::<LittleEndian>()?,
+                        try_offset: cur.read_u32::<LittleEndian>()?,
+                        try_length: cur.read_u32::<LittleEndian>()?,
+                        handler_offset: cur.read_u32::<LittleEndian>()?,
+                        handler_length: cur.read_u32::<LittleEndian>()?,
+                        class_token_or_filter_offset: cur.read_u32::<LittleEndian>()?,
+                    }
+                } else {
+                    RawClause {
+                        flags: cur.read_u16::<LittleEndian>()? as u32,
+                        try_offset: cur.read_u16::<LittleEndian>()? as u32,
+                        try_length: cur.read_u8()? as u32,
+                        handler_offset: cur.read_u16::<LittleEndian>()? as u32,
+                        handler_length: cur.read_u8()? as u32,
+                        class_token_or_filter_offset: cur.read_u32::<LittleEndian>()?,
+                    }
+                });
+            }
+        } else {
+            // skip unknown section (OptILTable is reserved)
+            let skip = (data_size - 4) as usize;
+            if cur.len() < skip {
+                return Err("trying to read beyond end of slice".into());
+            }
+            *cur = &cur[skip..];
+        }
+
+        if kind & bits::CorILMethod_Sect_MoreSects == 0 {
+            break;
+        }
+
+        // the next section is 4-byte aligned again
+        let padding = (4 - (data_size as usize % 4)) % 4;
+        if cur.len() < padding {
+            return Err("trying to read beyond end of slice".into());
+        }
+        *cur = &cur[padding..];
+    }
+
+    Ok(clauses)
+}
+
+// ECMA-335, II.25.4
+#[derive(Clone)]
+pub struct MethodBody<'db> {
+    m_db: &'db Database<'db>,
+    m_flags: u16,
+    m_max_stack: u16,
+    m_local_var_sig_token: u32,
+    m_local_var_sig: Option<StandAloneSig<'db>>,
+    m_code: &'db [u8],
+    m_exception_clauses: Vec<ExceptionClause<'db>>,
+}
+
+impl<'db> MethodBody<'db> {
+    pub(crate) fn parse(data: &'db [u8], db: &'db Database<'db>) -> Result<MethodBody<'db>> {
+        let mut cur = data;
+        let header = Header::parse(&mut cur)?;
+
+        if cur.len() < header.code_size as usize {
+            return Err("Method body exceeds section".into());
+        }
+        let (code, rest) = cur.split_at(header.code_size as usize);
+
+        let mut exception_clauses = Vec::new();
+        if header.flags & bits::CorILMethod_MoreSects != 0 {
+            // the first data section is 4-byte aligned relative to the beginning of the method body
+            let consumed = data.len() - rest.len();
+            let padding = (4 - consumed % 4) % 4;
+            if rest.len() < padding {
+                return Err("trying to read beyond end of slice".into());
+            }
+            let mut cur = &rest[padding..];
+            for raw in parse_sections(&mut cur)? {
+                exception_clauses.push(ExceptionClause::from_raw(raw, db)?);
+            }
+        }
+
+        let local_var_sig = if header.local_var_sig_token != 0 {
+            if header.local_var_sig_token >> 24 != 0x11 {
+                return Err("LocalVarSigTok must reference the StandAloneSig table".into());
+            }
+            let row = header.local_var_sig_token & 0x00ff_ffff;
+            if row == 0 { None } else { Some(db.get_table::<StandAloneSig>().get_row(row - 1)?) }
+        } else {
+            None
+        };
+
+        Ok(MethodBody {
+            m_db: db,
+            m_flags: header.flags,
+            m_max_stack: header.max_stack,
+            m_local_var_sig_token: header.local_var_sig_token,
+            m_local_var_sig: local_var_sig,
+            m_code: code,
+            m_exception_clauses: exception_clauses
+        })
+    }
+
+    pub fn max_stack(&self) -> u16 {
+        self.m_max_stack
+    }
+
+    pub fn init_locals(&self) -> bool {
+        self.m_flags & bits::CorILMethod_InitLocals != 0
+    }
+
+    pub fn local_var_sig_token(&self) -> u32 {
+        self.m_local_var_sig_token
+    }
+
+    pub fn local_var_sig(&self) -> Option<&StandAloneSig<'db>> {
+        self.m_local_var_sig.as_ref()
+    }
+
+    pub fn code(&self) -> &'db [u8] {
+        self.m_code
+    }
+
+    pub fn exception_clauses(&self) -> &[ExceptionClause<'db>] {
+        &self.m_exception_clauses[..]
+    }
+
+    pub fn instructions(&self) -> Instructions<'db> {
+        Instructions::new(self.m_code, self.m_db)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ExceptionClauseKind<'db> {
+    Catch(TypeDefOrRef<'db>),
+    Filter(u32), // offset of the filter block
+    Finally,
+    Fault,
+}
+
+// ECMA-335, II.25.4.6
+#[derive(Clone, Debug)]
+pub struct ExceptionClause<'db> {
+    m_kind: ExceptionClauseKind<'db>,
+    m_try_offset: u32,
+    m_try_length: u32,
+    m_handler_offset: u32,
+    m_handler_length: u32,
+}
+
+impl<'db> ExceptionClause<'db> {
+    fn from_raw(raw: RawClause, db: &'db Database<'db>) -> Result<ExceptionClause<'db>> {
+        let kind = match raw.flags {
+            bits::COR_ILEXCEPTION_CLAUSE_EXCEPTION => {
+                let typ = TypeDefOrRef::from_token(raw.class_token_or_filter_offset, db)?;
+                ExceptionClauseKind::Catch(typ.ok_or(crate::DecodeError("Null type in exception clause"))?)
+            },
+            bits::COR_ILEXCEPTION_CLAUSE_FILTER => ExceptionClauseKind::Filter(raw.class_token_or_filter_offset),
+            bits::COR_ILEXCEPTION_CLAUSE_FINALLY => ExceptionClauseKind::Finally,
+            bits::COR_ILEXCEPTION_CLAUSE_FAULT => ExceptionClauseKind::Fault,
+            _ => return Err("Invalid exception clause flags".into())
+        };
+
+        Ok(ExceptionClause {
+            m_kind: kind,
+            m_try_offset: raw.try_offset,
+            m_try_length: raw.try_length,
+            m_handler_offset: raw.handler_offset,
+            m_handler_length: raw.handler_length
+        })
+    }
+
+    pub fn kind(&self) -> &ExceptionClauseKind<'db> {
+        &self.m_kind
+    }
+
+    pub fn try_offset(&self) -> u32 {
+        self.m_try_offset
+    }
+
+    pub fn try_length(&self) -> u32 {
+        self.m_try_length
+    }
+
+    pub fn handler_offset(&self) -> u32 {
+        self.m_handler_offset
+    }
+
+    pub fn handler_length(&self) -> u32 {
+        self.m_handler_length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Header, parse_sections};
+
+    #[test]
+    fn test_parse_tiny_header() {
+        let mut data: &[u8] = &[0x1a, 0x2a, 0x00];
+        let header = Header::parse(&mut data).unwrap();
+        assert_eq!(header.code_size, 6);
+        assert_eq!(header.max_stack, 8);
+        assert_eq!(header.local_var_sig_token, 0);
+        assert_eq!(data, &[0x2a, 0x00]);
+    }
+
+    #[test]
+    fn test_parse_fat_header() {
+        let mut data: &[u8] = &[0x1b, 0x30, 0x03, 0x00, 0x10, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x11, 0x2a];
+        let header = Header::parse(&mut data).unwrap();
+        assert_eq!(header.flags, 0x01b);
+        assert_eq!(header.max_stack, 3);
+        assert_eq!(header.code_size, 16);
+        assert_eq!(header.local_var_sig_token, 0x11000001);
+        assert_eq!(data, &[0x2a]);
+        assert!(Header::parse(&mut &[0x01][..]).is_err());
+    }
+
+    #[test]
+    fn test_parse_sections() {
+        // small EH table with a single finally clause
+        let mut data: &[u8] = &[0x01, 0x10, 0x00, 0x00,
+                                0x02, 0x00, 0x01, 0x00, 0x08, 0x09, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00];
+        let clauses = parse_sections(&mut data).unwrap();
+        assert_eq!(clauses.len(), 1);
+        assert_eq!(clauses[0].flags, 2);
+        assert_eq!(clauses[0].try_offset, 1);
+        assert_eq!(clauses[0].try_length, 8);
+        assert_eq!(clauses[0].handler_offset, 9);
+        assert_eq!(clauses[0].handler_length, 3);
+
+        // fat EH table with a single filter clause
+        let mut data: &[u8] = &[0x41, 0x1c, 0x00, 0x00,
+                                0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00,
+                                0x20, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00];
+        let clauses = parse_sections(&mut data).unwrap();
+        assert_eq!(clauses.len(), 1);
+        assert_eq!(clauses[0].flags, 1);
+        assert_eq!(clauses[0].try_length, 16);
+        assert_eq!(clauses[0].handler_offset, 32);
+        assert_eq!(clauses[0].class_token_or_filter_offset, 16);
+    }
+}
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::collections::HashSet;
 
 use num_traits::FromPrimitive;
 use byteorder::{ByteOrder, LittleEndian};
@@ -10,13 +11,17 @@ use crate::core::table::{Row, TableRowIterator};
 use crate::core::ByteView;
 use crate::schema;
 use crate::schema::marker;
+use crate::schema::fmt_or_invalid;
 use crate::schema::signatures::*;
 use crate::schema::flags::*;
-use crate::schema::custom_attributes::CustomAttributeSig;
+use crate::schema::custom_attributes::{CustomAttributeSig, FromCustomAttribute};
+use crate::schema::method_body::MethodBody;
+use crate::schema::marshal::MarshalSpec;
+use crate::schema::security::{SecurityAction, PermissionSet};
 
 macro_rules! row_type {
     ($ty:ident) => {
-        #[derive(Clone)]
+        #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
         pub struct $ty<'db>(pub(crate) Row<'db, schema::marker::$ty>);
 
         impl<'db> crate::TableRow for $ty<'db> {
@@ -78,8 +83,87 @@ row_type!(TypeDef);
 row_type!(TypeRef);
 row_type!(TypeSpec);
 
+/// Implemented by all rows that can have custom attributes (see the `HasCustomAttribute` coded index).
+pub trait HasCustomAttributes<'db> {
+    fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>>;
+
+    /// Returns the first attribute of the given type.
+    fn get_attribute(&self, type_namespace: &str, type_name: &str) -> Result<Option<CustomAttribute<'db>>> {
+        for attr in self.custom_attributes()? {
+            if attr.namespace_name_pair() == (type_namespace, type_name) {
+                return Ok(Some(attr));
+            }
+        }
+        Ok(None)
+    }
+
+    fn has_attribute(&self, type_namespace: &str, type_name: &str) -> Result<bool> {
+        Ok(self.get_attribute(type_namespace, type_name)?.is_some())
+    }
+
+    /// Returns all attributes of the given type (some attributes may be applied multiple times).
+    fn attributes_of_type(&self, type_namespace: &str, type_name: &str) -> Result<Vec<CustomAttribute<'db>>> {
+        let mut result = Vec::new();
+        for attr in self.custom_attributes()? {
+            if attr.namespace_name_pair() == (type_namespace, type_name) {
+                result.push(attr);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the decoded value of the first attribute of the given type.
+    fn attribute_value<'c: 'db>(&self, type_namespace: &str, type_name: &str, cache: &Cache<'c>) -> Result<Option<CustomAttributeSig<'db>>> {
+        match self.get_attribute(type_namespace, type_name)? {
+            Some(attr) => Ok(Some(attr.value(cache)?)),
+            None => Ok(None)
+        }
+    }
+
+    /// Returns the first attribute of type `T`, converted with `FromCustomAttribute`.
+    fn typed_attribute<'c: 'db, T: FromCustomAttribute<'db>>(&self, cache: &Cache<'c>) -> Result<Option<T>> {
+        match self.attribute_value(T::TYPE_NAMESPACE, T::TYPE_NAME, cache)? {
+            Some(sig) => Ok(Some(T::from_custom_attribute(&sig)?)),
+            None => Ok(None)
+        }
+    }
+}
+
+macro_rules! impl_has_custom_attributes {
+    ($($ty:ident),+) => {
+        $(
+            impl<'db> HasCustomAttributes<'db> for $ty<'db> {
+                fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
+                    self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+                }
+            }
+        )+
+    }
+}
+
+// ECMA-335, II.24.2.6 (all tables in the HasCustomAttribute coded index, except for Permission)
+impl_has_custom_attributes!(MethodDef, Field, TypeRef, TypeDef, Param, InterfaceImpl, MemberRef, Module,
+                            Property, Event, StandAloneSig, ModuleRef, TypeSpec, Assembly, AssemblyRef,
+                            File, ExportedType, ManifestResource, GenericParam, GenericParamConstraint, MethodSpec);
+
 // ECMA-335, II.22.2
 impl<'db> Assembly<'db> {
+    pub fn decl_security(&self) -> Result<TableRowIterator<'db, marker::DeclSecurity>> {
+        self.0.get_list_by_key::<marker::DeclSecurity>(super::HasDeclSecurity::encode(self))
+    }
+
+    pub fn hash_alg_id(&self) -> Result<AssemblyHashAlgorithm> {
+        <AssemblyHashAlgorithm as FromPrimitive>::from_u32(self.0.get_value::<Col0, _>()?).ok_or_else(|| "Invalid AssemblyHashAlgorithm".into())
+    }
+
+    pub fn version(&self) -> Result<super::Version> {
+        Ok(super::Version::from_u64(self.0.get_value::<Col1, _>()?))
+    }
+
+    pub fn flags(&self) -> Result<AssemblyAttributes> {
+        Ok(AssemblyAttributes(self.0.get_value::<Col2, _>()?))
+    }
+
     pub fn public_key(&self) -> Result<Option<&'db [u8]>> {
         self.0.get_blob::<Col3>()
     }
@@ -92,8 +176,25 @@ impl<'db> Assembly<'db> {
         self.0.get_string::<Col5>()
     }
 
+    #[cfg(feature = "strong-name")]
+    pub fn public_key_token(&self) -> Result<Option<[u8; 8]>> {
+        match self.public_key()? {
+            Some(key) if !key.is_empty() => Ok(Some(super::public_key_token(key))),
+            _ => Ok(None)
+        }
+    }
+
+    pub fn identity(&self) -> Result<super::AssemblyName<'db>> {
+        Ok(super::AssemblyName {
+            name: self.name()?,
+            version: self.version()?,
+            culture: self.culture()?,
+            public_key: self.public_key()?.filter(|k| !k.is_empty()).map(super::PublicKeyOrToken::PublicKey)
+        })
+    }
+
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
@@ -109,6 +210,14 @@ impl<'db> AssemblyProcessor<'db> {
 
 // ECMA-335, II.22.5
 impl<'db> AssemblyRef<'db> {
+    pub fn version(&self) -> Result<super::Version> {
+        Ok(super::Version::from_u64(self.0.get_value::<Col0, _>()?))
+    }
+
+    pub fn flags(&self) -> Result<AssemblyAttributes> {
+        Ok(AssemblyAttributes(self.0.get_value::<Col1, _>()?))
+    }
+
     pub fn public_key_or_token(&self) -> Result<Option<&'db [u8]>> {
         self.0.get_blob::<Col2>()
     }
@@ -121,12 +230,35 @@ impl<'db> AssemblyRef<'db> {
         self.0.get_string::<Col4>()
     }
 
-    pub fn hash_value(&self) -> Result<&'db str> {
-        self.0.get_string::<Col5>()
+    pub fn hash_value(&self) -> Result<Option<&'db [u8]>> {
+        self.0.get_blob::<Col5>()
+    }
+
+    /// Returns the public key token, which is computed if the reference holds the full public key.
+    #[cfg(feature = "strong-name")]
+    pub fn public_key_token(&self) -> Result<Option<[u8; 8]>> {
+        self.identity()?.public_key_token()
+    }
+
+    pub fn identity(&self) -> Result<super::AssemblyName<'db>> {
+        let public_key = match self.public_key_or_token()? {
+            Some(blob) if !blob.is_empty() => Some(if self.flags()?.public_key() {
+                super::PublicKeyOrToken::PublicKey(blob)
+            } else {
+                super::PublicKeyOrToken::Token(blob)
+            }),
+            _ => None
+        };
+        Ok(super::AssemblyName {
+            name: self.name()?,
+            version: self.version()?,
+            culture: self.culture()?,
+            public_key
+        })
     }
 
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
@@ -142,7 +274,17 @@ impl<'db> AssemblyRefProcessor<'db> {
 
 // ECMA-335, II.22.8
 impl<'db> ClassLayout<'db> {
-    // TODO
+    pub fn packing_size(&self) -> Result<u16> {
+        self.0.get_value::<Col0, _>()
+    }
+
+    pub fn class_size(&self) -> Result<u32> {
+        self.0.get_value::<Col1, _>()
+    }
+
+    pub fn parent(&self) -> Result<TypeDef<'db>> {
+        self.0.get_target_row::<Col2, marker::TypeDef>()
+    }
 }
 
 // ECMA-335, II.22.9
@@ -224,7 +366,7 @@ impl<'db> ResolveToTypeDef<'db> for CustomAttribute<'db> {
                         _ => return Err("A CustomAttribute MemberRef should only be a TypeDef or TypeRef".into())
                     }
                 },
-                super::CustomAttributeType::MethodDef(ref md) => unimplemented!() // md.parent()?.namespace_name_pair()
+                super::CustomAttributeType::MethodDef(ref md) => md.parent()?.namespace_name_pair()
             })
         }
 
@@ -237,12 +379,30 @@ impl<'db> ResolveToTypeDef<'db> for CustomAttribute<'db> {
 
 // ECMA-335, II.22.11
 impl<'db> DeclSecurity<'db> {
-    // TODO
+    pub fn action(&self) -> Result<SecurityAction> {
+        <SecurityAction as FromPrimitive>::from_u16(self.0.get_value::<Col0, _>()?).ok_or_else(|| "Invalid SecurityAction".into())
+    }
+
+    pub fn parent(&self) -> Result<super::HasDeclSecurity<'db>> {
+        self.0.get_coded_index::<Col1, super::HasDeclSecurity>()?.ok_or(crate::DecodeError("DeclSecurity Parent column must not be NULL"))
+    }
+
+    /// Decodes the permission set. Enum values in binary permission sets are resolved via `cache`.
+    pub fn permission_set<'c: 'db>(&self, cache: &Cache<'c>) -> Result<PermissionSet<'db>> {
+        let mut blob = self.0.get_blob::<Col2>()?.ok_or(crate::DecodeError("DeclSecurity PermissionSet blob was NULL"))?;
+        PermissionSet::parse(&mut blob, self.0.m_table.db, cache)
+    }
 }
 
 // ECMA-335, II.22.12
 impl<'db> EventMap<'db> {
-    // TODO
+    pub fn parent(&self) -> Result<TypeDef<'db>> {
+        self.0.get_target_row::<Col0, marker::TypeDef>()
+    }
+
+    pub fn event_list(&self) -> Result<TableRowIterator<'db, marker::Event>> {
+        self.0.get_list::<Col1, marker::Event>()
+    }
 }
 
 // ECMA-335, II.22.13
@@ -251,15 +411,23 @@ impl<'db> Event<'db> {
         Ok(EventAttributes(self.0.get_value::<Col0, _>()?))
     }
 
+    pub fn name(&self) -> Result<&'db str> {
+        self.0.get_string::<Col1>()
+    }
+
+    pub fn event_type(&self) -> Result<Option<super::TypeDefOrRef<'db>>> {
+        self.0.get_coded_index::<Col2, super::TypeDefOrRef>()
+    }
+
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
 // ECMA-335, II.22.14
 impl<'db> ExportedType<'db> {
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
@@ -281,30 +449,119 @@ impl<'db> Field<'db> {
         self.0.get_single_by_key::<marker::Constant>(super::HasConstant::encode(self))
     }
 
+    /// Returns the type that declares this field.
+    pub fn parent(&self) -> Result<TypeDef<'db>> {
+        self.0.get_list_owner::<Col4, marker::TypeDef>()
+    }
+
+    /// Returns the offset of the field within a type with explicit layout.
+    pub fn explicit_offset(&self) -> Result<Option<u32>> {
+        match self.0.get_single_by_key::<marker::FieldLayout>(self.0.get_index() + 1)? {
+            Some(layout) => Ok(Some(layout.offset()?)),
+            None => Ok(None)
+        }
+    }
+
+    /// Returns how the field is marshalled to unmanaged code, if specified.
+    pub fn marshal(&self) -> Result<Option<MarshalSpec<'db>>> {
+        match self.0.get_single_by_key::<marker::FieldMarshal>(super::HasFieldMarshal::encode(self))? {
+            Some(marshal) => Ok(Some(marshal.native_type()?)),
+            None => Ok(None)
+        }
+    }
+
+    pub fn field_rva(&self) -> Result<Option<FieldRVA<'db>>> {
+        self.0.get_single_by_key::<marker::FieldRVA>(self.0.get_index() + 1)
+    }
+
+    /// Returns the data that a field with an RVA is initialized with, e.g. the contents of a
+    /// static array initializer. The size is taken from the field type, which must be a
+    /// primitive type, an enum or a value type with an explicit `ClassLayout` size. Value
+    /// types defined in other databases are resolved through the cache.
+    pub fn initial_data<'c: 'db>(&self, cache: &Cache<'c>) -> Result<Option<&'db [u8]>> {
+        let field_rva = match self.field_rva()? {
+            Some(field_rva) => field_rva,
+            None => return Ok(None)
+        };
+        let size = match self.signature()?.type_() {
+            Type::Primitive(p) => p.size(),
+            Type::Ref(TypeTag::ValueType, t @ super::TypeDefOrRef::TypeDef(_), None) |
+            Type::Ref(TypeTag::ValueType, t @ super::TypeDefOrRef::TypeRef(_), None) => {
+                let t = t.resolve(cache).ok_or(crate::DecodeError("Value type of FieldRVA field can not be resolved"))?;
+                if t.is_enum() {
+                    t.enum_get_underlying_type()?.size()
+                } else {
+                    match t.class_layout()? {
+                        Some(layout) if layout.class_size()? > 0 => Some(layout.class_size()?),
+                        _ => None
+                    }
+                }
+            },
+            _ => None
+        };
+        let size = size.ok_or(crate::DecodeError("Size of FieldRVA data can not be determined from the field type"))?;
+        Ok(Some(self.0.m_table.db.read_rva(field_rva.rva()?, size)?))
+    }
+
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
+    }
+}
+
+impl<'db> fmt::Debug for Field<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Field(\"{}\")", self.name().unwrap_or("<invalid>"))
+    }
+}
+
+impl<'db> fmt::Display for Field<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.16.1
+        fmt_or_invalid(f, |f| {
+            let sig = self.signature()?;
+            let parent = self.parent()?;
+            Ok(write!(f, "{} {}::{}", sig, parent, self.name()?))
+        })
     }
 }
 
 // ECMA-335, II.22.16
 impl<'db> FieldLayout<'db> {
-    // TODO
+    pub fn offset(&self) -> Result<u32> {
+        self.0.get_value::<Col0, _>()
+    }
+
+    pub fn field(&self) -> Result<Field<'db>> {
+        self.0.get_target_row::<Col1, marker::Field>()
+    }
 }
 
 // ECMA-335, II.22.17
 impl<'db> FieldMarshal<'db> {
-    // TODO
+    pub fn parent(&self) -> Result<super::HasFieldMarshal<'db>> {
+        self.0.get_coded_index::<Col0, super::HasFieldMarshal>()?.ok_or(crate::DecodeError("FieldMarshal Parent column must not be NULL"))
+    }
+
+    pub fn native_type(&self) -> Result<MarshalSpec<'db>> {
+        MarshalSpec::parse(&mut self.0.get_blob::<Col1>()?.ok_or(crate::DecodeError("FieldMarshal NativeType blob was NULL"))?)
+    }
 }
 
 // ECMA-335, II.22.18
 impl<'db> FieldRVA<'db> {
-    // TODO
+    pub fn rva(&self) -> Result<u32> {
+        self.0.get_value::<Col0, _>()
+    }
+
+    pub fn field(&self) -> Result<Field<'db>> {
+        self.0.get_target_row::<Col1, marker::Field>()
+    }
 }
 
 // ECMA-335, II.22.19
 impl<'db> File<'db> {
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
@@ -327,33 +584,57 @@ impl<'db> GenericParam<'db> {
     }
 
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
 // ECMA-335, II.22.21
 impl<'db> GenericParamConstraint<'db> {
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
 // ECMA-335, II.22.22
 impl<'db> ImplMap<'db> {
-    // TODO
+    pub fn flags(&self) -> Result<PInvokeAttributes> {
+        Ok(PInvokeAttributes(self.0.get_value::<Col0, _>()?))
+    }
+
+    pub fn member_forwarded(&self) -> Result<super::MemberForwarded<'db>> {
+        self.0.get_coded_index::<Col1, super::MemberForwarded>()?.ok_or(crate::DecodeError("ImplMap MemberForwarded column must not be NULL"))
+    }
+
+    /// The name of the function in the native library.
+    pub fn import_name(&self) -> Result<&'db str> {
+        self.0.get_string::<Col2>()
+    }
+
+    /// The native library that the function is imported from.
+    pub fn import_scope(&self) -> Result<ModuleRef<'db>> {
+        self.0.get_target_row::<Col3, marker::ModuleRef>()
+    }
 }
 
 // ECMA-335, II.22.23
 impl<'db> InterfaceImpl<'db> {
+    pub fn class(&self) -> Result<TypeDef<'db>> {
+        self.0.get_target_row::<Col0, marker::TypeDef>()
+    }
+
+    pub fn interface(&self) -> Result<super::TypeDefOrRef<'db>> {
+        Ok(self.0.get_coded_index::<Col1, super::TypeDefOrRef>()?.expect("InterfaceImpl Interface column must not be NULL"))
+    }
+
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
 // ECMA-335, II.22.24
 impl<'db> ManifestResource<'db> {
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
@@ -367,13 +648,49 @@ impl<'db> MemberRef<'db> {
         self.0.get_string::<Col1>()
     }
 
+    pub fn signature(&self) -> Result<MemberRefSig<'db>> {
+        MemberRefSig::parse(&mut self.0.get_blob::<Col2>()?.ok_or(crate::DecodeError("MemberRef signature blob was NULL"))?, self.0.m_table.db)
+    }
+
+    /// Returns the method signature without any vararg parameters, or an error
+    /// if this MemberRef references a field.
     pub fn method_signature(&self) -> Result<MethodDefSig<'db>> {
-        // FIXME: this could also be a field signature (FieldSig) ...
-        MethodDefSig::parse(&mut self.0.get_blob::<Col2>()?.expect("MemberRef signature blob was NULL"), self.0.m_table.db)
+        match self.signature()? {
+            MemberRefSig::Method(sig) => Ok(sig.method_sig().clone()),
+            MemberRefSig::Field(_) => Err("MemberRef references a field, not a method".into())
+        }
+    }
+
+    pub(crate) fn is_field(&self) -> Result<bool> {
+        let blob = self.0.get_blob::<Col2>()?.ok_or(crate::DecodeError("MemberRef signature blob was NULL"))?;
+        Ok(blob.first() == Some(&crate::schema::signatures::bits::FIELD))
     }
 
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
+    }
+}
+
+impl<'db> fmt::Debug for MemberRef<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MemberRef(\"{}\")", self.name().unwrap_or("<invalid>"))
+    }
+}
+
+impl<'db> fmt::Display for MemberRef<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.15.4.2 and II.16.1
+        fmt_or_invalid(f, |f| {
+            let class = self.class()?;
+            let name = self.name()?;
+            Ok(match self.signature()? {
+                MemberRefSig::Method(sig) => {
+                    let qualified = format!("{}::{}", class, name);
+                    fmt_method_sig(sig.method_sig(), sig.vararg_params(), Some(&qualified), f)
+                },
+                MemberRefSig::Field(sig) => write!(f, "{} {}::{}", sig, class, name)
+            })
+        })
     }
 }
 
@@ -402,14 +719,109 @@ impl<'db> MethodDef<'db> {
         self.0.get_list::<Col5, marker::Param>()
     }
 
+    /// Returns the type that declares this method.
+    pub fn parent(&self) -> Result<TypeDef<'db>> {
+        self.0.get_list_owner::<Col5, marker::TypeDef>()
+    }
+
+    /// Returns the CIL method body, or `None` if the method has no body
+    /// (e.g. because it is abstract or implemented by the runtime).
+    pub fn body(&self) -> Result<Option<MethodBody<'db>>> {
+        let rva = self.rva()?;
+        if rva == 0 || self.impl_flags()?.code_type() != CodeType::IL {
+            return Ok(None);
+        }
+        let db = self.0.m_table.db;
+        Ok(Some(MethodBody::parse(db.get_rva_data(rva)?, db)?))
+    }
+
+    /// Returns the import information of a method that is implemented in a native library (P/Invoke).
+    pub fn pinvoke_info(&self) -> Result<Option<ImplMap<'db>>> {
+        self.0.get_single_by_key::<marker::ImplMap>(super::MemberForwarded::encode(self))
+    }
+
+    pub fn decl_security(&self) -> Result<TableRowIterator<'db, marker::DeclSecurity>> {
+        self.0.get_list_by_key::<marker::DeclSecurity>(super::HasDeclSecurity::encode(self))
+    }
+
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
+    }
+}
+
+impl<'db> fmt::Debug for MethodDef<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MethodDef(\"{}\")", self.name().unwrap_or("<invalid>"))
+    }
+}
+
+impl<'db> fmt::Display for MethodDef<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.15.4.2
+        fmt_or_invalid(f, |f| {
+            let sig = self.signature()?;
+            let qualified = format!("{}::{}", self.parent()?, self.name()?);
+            Ok(fmt_method_sig(&sig, &[], Some(&qualified), f))
+        })
     }
 }
 
 // ECMA-335, II.22.27
 impl<'db> MethodImpl<'db> {
-    // TODO
+    pub fn class(&self) -> Result<TypeDef<'db>> {
+        self.0.get_target_row::<Col0, marker::TypeDef>()
+    }
+
+    /// The method that provides the implementation.
+    pub fn method_body(&self) -> Result<super::MethodDefOrRef<'db>> {
+        self.0.get_coded_index::<Col1, super::MethodDefOrRef>()?.ok_or(crate::DecodeError("MethodImpl MethodBody column must not be NULL"))
+    }
+
+    /// The (interface or base class) method that is implemented.
+    pub fn method_declaration(&self) -> Result<super::MethodDefOrRef<'db>> {
+        self.0.get_coded_index::<Col2, super::MethodDefOrRef>()?.ok_or(crate::DecodeError("MethodImpl MethodDeclaration column must not be NULL"))
+    }
+
+    // Checks whether the declaration is `method` of `interface`, where generic arguments
+    // of the declaring type are substituted with `type_args`
+    fn declares(&self, interface: &Type<'db>, method: &MethodDef<'db>, type_args: &[Type<'db>]) -> Result<bool> {
+        Ok(match self.method_declaration()? {
+            super::MethodDefOrRef::MethodDef(decl) => decl == *method,
+            super::MethodDefOrRef::MemberRef(decl) => {
+                let parent = match decl.class()? {
+                    super::MemberRefParent::TypeDef(t) => Type::Ref(TypeTag::Class, super::TypeDefOrRef::TypeDef(t), None),
+                    super::MemberRefParent::TypeRef(t) => Type::Ref(TypeTag::Class, super::TypeDefOrRef::TypeRef(t), None),
+                    super::MemberRefParent::TypeSpec(t) => instantiate_typedeforref(&super::TypeDefOrRef::TypeSpec(t), type_args)?,
+                    _ => return Ok(false)
+                };
+                parent == *interface && decl.name()? == method.name()? && !decl.is_field()?
+                    && decl.method_signature()? == method.signature()?
+            }
+        })
+    }
+
+    // Returns the implementing method. A MemberRef body, e.g. a method of a generic base
+    // type, is resolved through `cache` with generic arguments substituted with `type_args`
+    fn resolve_body<'c: 'db>(&self, type_args: &[Type<'db>], cache: &Cache<'c>) -> Result<MethodDef<'db>> {
+        let body = match self.method_body()? {
+            super::MethodDefOrRef::MethodDef(body) => return Ok(body),
+            super::MethodDefOrRef::MemberRef(body) => body
+        };
+        let parent = match body.class()? {
+            super::MemberRefParent::TypeDef(t) => Some(t),
+            super::MemberRefParent::TypeRef(t) => t.resolve(cache),
+            super::MemberRefParent::TypeSpec(t) => {
+                let parent = instantiate_typedeforref(&super::TypeDefOrRef::TypeSpec(t), type_args)?;
+                resolve_type(&parent, cache).map(|(def, _)| def)
+            },
+            _ => None
+        };
+        let method = match parent {
+            Some(parent) => parent.find_method_with_signature(body.name()?, &body.method_signature()?)?,
+            None => None
+        };
+        method.ok_or(crate::DecodeError("MethodImpl body can not be resolved to a MethodDef"))
+    }
 }
 
 // ECMA-335, II.22.28
@@ -429,8 +841,32 @@ impl<'db> MethodSemantics<'db> {
 
 // ECMA-335, II.22.29
 impl<'db> MethodSpec<'db> {
+    pub fn method(&self) -> Result<super::MethodDefOrRef<'db>> {
+        self.0.get_coded_index::<Col0, super::MethodDefOrRef>()?.ok_or(crate::DecodeError("MethodSpec Method column must not be NULL"))
+    }
+
+    pub fn instantiation(&self) -> Result<MethodSpecSig<'db>> {
+        MethodSpecSig::parse(&mut self.0.get_blob::<Col1>()?.ok_or(crate::DecodeError("MethodSpec instantiation blob was NULL"))?, self.0.m_table.db)
+    }
+
+    /// Returns the signature of the generic method with its method type parameters (`!!n`)
+    /// replaced by the type arguments of this instantiation.
+    pub fn instantiated_signature(&self) -> Result<MethodDefSig<'db>> {
+        let sig = match self.method()? {
+            super::MethodDefOrRef::MethodDef(m) => m.signature()?,
+            super::MethodDefOrRef::MemberRef(m) => m.method_signature()?,
+        };
+        Ok(sig.instantiate(&[], self.instantiation()?.generic_args()))
+    }
+
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
+    }
+}
+
+impl<'db> fmt::Debug for MethodSpec<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MethodSpec(#{})", self.0.get_index() + 1)
     }
 }
 
@@ -441,20 +877,26 @@ impl<'db> Module<'db> {
     }
 
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
 // ECMA-335, II.22.31
 impl<'db> ModuleRef<'db> {
+    pub fn name(&self) -> Result<&'db str> {
+        self.0.get_string::<Col0>()
+    }
+
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
 // ECMA-335, II.22.32
 impl<'db> NestedClass<'db> {
-    // TODO
+    pub(crate) fn enclosing_class(&self) -> Result<TypeDef<'db>> {
+        self.0.get_target_row::<Col1, marker::TypeDef>()
+    }
 }
 
 
@@ -472,8 +914,17 @@ impl<'db> Param<'db> {
         self.0.get_string::<Col2>()
     }
 
+    /// Returns how the parameter (or the return value, for sequence 0) is marshalled
+    /// to unmanaged code, if specified.
+    pub fn marshal(&self) -> Result<Option<MarshalSpec<'db>>> {
+        match self.0.get_single_by_key::<marker::FieldMarshal>(super::HasFieldMarshal::encode(self))? {
+            Some(marshal) => Ok(Some(marshal.native_type()?)),
+            None => Ok(None)
+        }
+    }
+
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
@@ -483,20 +934,40 @@ impl<'db> Property<'db> {
         Ok(PropertyAttributes(self.0.get_value::<Col0, _>()?))
     }
 
+    pub fn name(&self) -> Result<&'db str> {
+        self.0.get_string::<Col1>()
+    }
+
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
 // ECMA-335, II.22.35
 impl<'db> PropertyMap<'db> {
-    // TODO
+    pub fn parent(&self) -> Result<TypeDef<'db>> {
+        self.0.get_target_row::<Col0, marker::TypeDef>()
+    }
+
+    pub fn property_list(&self) -> Result<TableRowIterator<'db, marker::Property>> {
+        self.0.get_list::<Col1, marker::Property>()
+    }
 }
 
 // ECMA-335, II.22.36
 impl<'db> StandAloneSig<'db> {
+    pub fn signature(&self) -> Result<StandAloneSignature<'db>> {
+        StandAloneSignature::parse(&mut self.0.get_blob::<Col0>()?.ok_or(crate::DecodeError("StandAloneSig signature blob was NULL"))?, self.0.m_table.db)
+    }
+
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
+    }
+}
+
+impl<'db> fmt::Debug for StandAloneSig<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StandAloneSig(#{})", self.0.get_index() + 1)
     }
 }
 
@@ -526,6 +997,225 @@ impl<'db> TypeDef<'db> {
         self.0.get_list::<Col5, marker::MethodDef>()
     }
 
+    pub fn property_list(&self) -> Result<TableRowIterator<'db, marker::Property>> {
+        // the PropertyMap table is not sorted, so we need to search it linearly
+        for map in self.0.m_table.db.get_table::<PropertyMap>() {
+            if map.parent()? == *self {
+                return map.property_list();
+            }
+        }
+        Ok(TableRowIterator::empty(self.0.m_table.db.get_table::<Property>()))
+    }
+
+    pub fn event_list(&self) -> Result<TableRowIterator<'db, marker::Event>> {
+        // the EventMap table is not sorted, so we need to search it linearly
+        for map in self.0.m_table.db.get_table::<EventMap>() {
+            if map.parent()? == *self {
+                return map.event_list();
+            }
+        }
+        Ok(TableRowIterator::empty(self.0.m_table.db.get_table::<Event>()))
+    }
+
+    /// Returns the first method with the given name (see `find_methods` for overloads).
+    pub fn find_method(&self, name: &str) -> Result<Option<MethodDef<'db>>> {
+        for method in self.method_list()? {
+            if method.name()? == name {
+                return Ok(Some(method));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns all overloads of the method with the given name.
+    pub fn find_methods(&self, name: &str) -> Result<Vec<MethodDef<'db>>> {
+        let mut result = Vec::new();
+        for method in self.method_list()? {
+            if method.name()? == name {
+                result.push(method);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the overload of the method with the given name whose signature matches `signature`.
+    pub fn find_method_with_signature(&self, name: &str, signature: &MethodDefSig<'_>) -> Result<Option<MethodDef<'db>>> {
+        for method in self.find_methods(name)? {
+            if method.signature()? == *signature {
+                return Ok(Some(method));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn find_field(&self, name: &str) -> Result<Option<Field<'db>>> {
+        for field in self.field_list()? {
+            if field.name()? == name {
+                return Ok(Some(field));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn find_property(&self, name: &str) -> Result<Option<Property<'db>>> {
+        for property in self.property_list()? {
+            if property.name()? == name {
+                return Ok(Some(property));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn find_event(&self, name: &str) -> Result<Option<Event<'db>>> {
+        for event in self.event_list()? {
+            if event.name()? == name {
+                return Ok(Some(event));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn interface_impls(&self) -> Result<TableRowIterator<'db, marker::InterfaceImpl>> {
+        self.0.get_list_by_key::<marker::InterfaceImpl>(self.0.get_index() + 1)
+    }
+
+    /// Returns the declarative security (permission sets) attached to this type.
+    pub fn decl_security(&self) -> Result<TableRowIterator<'db, marker::DeclSecurity>> {
+        self.0.get_list_by_key::<marker::DeclSecurity>(super::HasDeclSecurity::encode(self))
+    }
+
+    /// Returns the explicit method implementations (overrides) of this type.
+    pub fn method_impls(&self) -> Result<TableRowIterator<'db, marker::MethodImpl>> {
+        self.0.get_list_by_key::<marker::MethodImpl>(self.0.get_index() + 1)
+    }
+
+    /// Maps each method of each interface implemented by this type (see `all_interfaces`)
+    /// to the method that implements it (ECMA-335, II.12.2). For the type and then each
+    /// of its base types, explicit implementations (`MethodImpl`) are considered before
+    /// public virtual methods with the same name and signature. Static interface members
+    /// are skipped, as are interfaces that cannot be resolved in `cache`. Fails if the body
+    /// of a matching `MethodImpl` is a `MemberRef` that cannot be resolved.
+    pub fn interface_map<'c: 'db>(&self, cache: &Cache<'c>) -> Result<Vec<super::InterfaceMethodImpl<'db>>> {
+        let mut levels = vec![(self.clone(), Box::default())];
+        for base in self.base_types(cache)? {
+            if let Some(level) = resolve_type(&base, cache) {
+                levels.push(level);
+            }
+        }
+
+        let mut result = Vec::new();
+        for interface in self.all_interfaces(cache)? {
+            let (interface_def, interface_args) = match resolve_type(&interface, cache) {
+                Some(resolved) => resolved,
+                None => continue
+            };
+            for declaration in interface_def.method_list()? {
+                let flags = declaration.flags()?;
+                if flags.static_() || !flags.virtual_() {
+                    continue;
+                }
+                let signature = declaration.signature()?.instantiate(&interface_args, &[]);
+                let mut implementation = None;
+                'levels: for (def, args) in &levels {
+                    for method_impl in def.method_impls()? {
+                        if method_impl.declares(&interface, &declaration, args)? {
+                            implementation = Some(method_impl.resolve_body(args, cache)?);
+                            break 'levels;
+                        }
+                    }
+                    for method in def.find_methods(declaration.name()?)? {
+                        let method_flags = method.flags()?;
+                        if method_flags.virtual_() && method_flags.access() == MemberAccess::Public
+                            && method.signature()?.instantiate(args, &[]) == signature {
+                            implementation = Some(method);
+                            break 'levels;
+                        }
+                    }
+                }
+                // default interface methods implement themselves
+                if implementation.is_none() && !flags.abstract_() {
+                    implementation = Some(declaration.clone());
+                }
+                result.push(super::InterfaceMethodImpl { interface: interface.clone(), declaration, implementation });
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn class_layout(&self) -> Result<Option<ClassLayout<'db>>> {
+        self.0.get_single_by_key::<marker::ClassLayout>(self.0.get_index() + 1)
+    }
+
+    /// Computes size, alignment and field offsets of a value type with sequential or explicit
+    /// layout for the given pointer size (4 or 8). Value types of fields are resolved via `cache`.
+    pub fn layout<'c: 'db>(&self, cache: &Cache<'c>, pointer_size: u32) -> Result<super::ValueTypeLayout<'db>> {
+        super::ValueTypeLayout::compute(self, cache, pointer_size)
+    }
+
+    // Returns the enclosing type if this is a nested type
+    pub(crate) fn enclosing_type(&self) -> Result<Option<TypeDef<'db>>> {
+        match self.0.get_single_by_key::<marker::NestedClass>(self.0.get_index() + 1)? {
+            Some(nested) => Ok(Some(nested.enclosing_class()?)),
+            None => Ok(None)
+        }
+    }
+
+    /// Returns the chain of base types, starting with the direct base type. Generic arguments
+    /// of base classes are substituted, e.g. `class B : A<!0>` used as `B<int32>` yields `A<int32>`.
+    /// The chain ends with the first base type that cannot be resolved in the cache. Returns an
+    /// error if the chain contains a cycle.
+    pub fn base_types<'c: 'db>(&self, cache: &Cache<'c>) -> Result<Vec<Type<'db>>> {
+        let mut result = Vec::new();
+        let mut visited = HashSet::new();
+        visited.insert(self.clone());
+        let mut current = self.clone();
+        let mut type_args: Box<[Type<'db>]> = Box::new([]);
+        while let Some(extends) = current.extends()? {
+            let base = instantiate_typedeforref(&extends, &type_args)?;
+            let resolved = resolve_type(&base, cache);
+            result.push(base);
+            match resolved {
+                Some((def, args)) => {
+                    if !visited.insert(def.clone()) {
+                        return Err("Cycle in base type chain".into());
+                    }
+                    current = def;
+                    type_args = args;
+                },
+                None => break
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns all interfaces implemented by this type, including those inherited from base
+    /// types and those required by other interfaces. Each interface is listed only once, with
+    /// generic arguments substituted.
+    pub fn all_interfaces<'c: 'db>(&self, cache: &Cache<'c>) -> Result<Vec<Type<'db>>> {
+        let mut result = Vec::new();
+        self.collect_interfaces(&[], cache, &mut result)?;
+        for base in self.base_types(cache)? {
+            if let Some((def, args)) = resolve_type(&base, cache) {
+                def.collect_interfaces(&args, cache, &mut result)?;
+            }
+        }
+        Ok(result)
+    }
+
+    fn collect_interfaces<'c: 'db>(&self, type_args: &[Type<'db>], cache: &Cache<'c>, result: &mut Vec<Type<'db>>) -> Result<()> {
+        for imp in self.interface_impls()? {
+            let interface = instantiate_typedeforref(&imp.interface()?, type_args)?;
+            if result.contains(&interface) {
+                continue;
+            }
+            result.push(interface.clone());
+            if let Some((def, args)) = resolve_type(&interface, cache) {
+                def.collect_interfaces(&args, cache, result)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn type_category(&self) -> Result<super::TypeCategory> {
         use super::TypeCategory::*;
         let result = if self.flags()?.semantics() == TypeSemantics::Interface {
@@ -579,18 +1269,32 @@ impl<'db> TypeDef<'db> {
     }
 
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 
-    // TODO: implement this for each type that has custom attributes (maybe via trait and blanket impl?)
-    pub fn get_attribute(&self, type_namespace: &str, type_name: &str) -> Result<Option<CustomAttribute>> {
-        for attr in self.custom_attributes()? {
-            let pair = attr.namespace_name_pair();
-            if pair == (type_namespace, type_name) {
-                return Ok(Some(attr));
+    pub fn get_attribute(&self, type_namespace: &str, type_name: &str) -> Result<Option<CustomAttribute<'db>>> {
+        <Self as HasCustomAttributes<'db>>::get_attribute(self, type_namespace, type_name)
+    }
+}
+
+// Turns the `extends` or `interface` column of a row into a type, substituting
+// the generic arguments of the derived type into generic instantiations
+fn instantiate_typedeforref<'db>(t: &super::TypeDefOrRef<'db>, type_args: &[Type<'db>]) -> Result<Type<'db>> {
+    Ok(match t {
+        super::TypeDefOrRef::TypeSpec(spec) => match spec.signature()? {
+            TypeSpecSig::GenericInst(tag, generic, args) => {
+                Type::Ref(tag, generic, Some(args.iter().map(|a| a.substitute(type_args, &[])).collect()))
             }
-        }
-        Ok(None)
+        },
+        _ => Type::Ref(TypeTag::Class, t.clone(), None)
+    })
+}
+
+// Resolves a class or interface type to its TypeDef and generic arguments
+fn resolve_type<'db, 'c: 'db>(t: &Type<'db>, cache: &Cache<'c>) -> Option<(TypeDef<'db>, Box<[Type<'db>]>)> {
+    match t {
+        Type::Ref(_, generic, args) => generic.resolve(cache).map(|def| (def, args.clone().unwrap_or_default())),
+        _ => None
     }
 }
 
@@ -614,6 +1318,28 @@ impl<'db> ResolveToTypeDef<'db> for TypeDef<'db> {
     }
 }
 
+impl<'db> fmt::Display for TypeDef<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.7.3 (nested types are written as `Outer/Inner`)
+        fmt_or_invalid(f, |f| {
+            let enclosing = self.enclosing_type()?;
+            let (namespace, name) = (self.type_namespace()?, self.type_name()?);
+            Ok(match enclosing {
+                Some(enclosing) => write!(f, "{}/", enclosing).and_then(|_| fmt_dotted_name(namespace, name, f)),
+                None => fmt_dotted_name(namespace, name, f)
+            })
+        })
+    }
+}
+
+fn fmt_dotted_name(namespace: &str, name: &str, f: &mut fmt::Formatter) -> fmt::Result {
+    if namespace.is_empty() {
+        write!(f, "{}", name)
+    } else {
+        write!(f, "{}.{}", namespace, name)
+    }
+}
+
 // ECMA-335, II.22.38
 impl<'db> TypeRef<'db> {
     pub fn resolution_scope(&self) -> Result<Option<super::ResolutionScope<'db>>> {
@@ -629,7 +1355,7 @@ impl<'db> TypeRef<'db> {
     }
 
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
     }
 }
 
@@ -649,6 +1375,23 @@ impl<'db> fmt::Debug for TypeRef<'db> {
     }
 }
 
+impl<'db> fmt::Display for TypeRef<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.7.3
+        use super::ResolutionScope;
+        fmt_or_invalid(f, |f| {
+            let scope = match self.resolution_scope()? {
+                Some(ResolutionScope::AssemblyRef(a)) => format!("[{}]", a.name()?),
+                Some(ResolutionScope::ModuleRef(m)) => format!("[.module {}]", m.name()?),
+                Some(ResolutionScope::TypeRef(t)) => format!("{}/", t),
+                Some(ResolutionScope::Module(_)) | None => String::new()
+            };
+            let (namespace, name) = (self.type_namespace()?, self.type_name()?);
+            Ok(write!(f, "{}", scope).and_then(|_| fmt_dotted_name(namespace, name, f)))
+        })
+    }
+}
+
 // ECMA-335, II.22.39
 impl<'db> TypeSpec<'db> {
     pub fn signature(&self) -> Result<TypeSpecSig<'db>> {
@@ -656,6 +1399,305 @@ impl<'db> TypeSpec<'db> {
     }
 
     pub fn custom_attributes(&self) -> Result<TableRowIterator<'db, marker::CustomAttribute>> {
-        self.0.get_list_by_key::<marker::CustomAttribute>(super::HasCustomAttribute::encode(self))
+        <Self as HasCustomAttributes<'db>>::custom_attributes(self)
+    }
+}
+
+impl<'db> fmt::Display for TypeSpec<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_or_invalid(f, |f| Ok(write!(f, "{}", self.signature()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cache, Database};
+    use crate::core::builder::{ImageBuilder, coded, hierarchy_image, CLASS, INTERFACE};
+
+    #[test]
+    fn test_base_types() {
+        let image = hierarchy_image();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+
+        let c = cache.find("N", "C").unwrap();
+        let bases: Vec<_> = c.base_types(&cache).unwrap().iter().map(|t| t.to_string()).collect();
+        // the generic argument of B<int32> is substituted into its base type A<!0>
+        assert_eq!(bases, ["class N.B`1<int32>", "class N.A`1<int32>"]);
+
+        let interfaces: Vec<_> = c.all_interfaces(&cache).unwrap().iter().map(|t| t.to_string()).collect();
+        assert_eq!(interfaces, ["class N.IFoo"]);
+
+        assert!(cache.find("N", "X").unwrap().base_types(&cache).is_err());
+    }
+
+    #[test]
+    fn test_display_invalid() {
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        builder.type_def(CLASS, "N", "Widget", 0, 1, 1);
+        builder.field(0x0001, "good", &[0x06, 0x08]);
+        builder.field(0x0001, "bad", &[0x20, 0x00, 0x01]); // not a field signature
+
+        let image = builder.build();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+        let widget = cache.find("N", "Widget").unwrap();
+        let fields: Vec<_> = widget.field_list().unwrap().map(|f| f.to_string()).collect();
+        assert_eq!(fields, ["int32 N.Widget::good", "<invalid>"]);
+        assert!(widget.field_list().unwrap().all(|f| f.parent().unwrap() == widget));
+    }
+
+    #[test]
+    fn test_initial_data() {
+        const RVA_FIELD: u64 = 0x0116; // public, static, has field RVA
+
+        // N.Block is a value type with an explicit size of 8 bytes
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        let block = builder.type_def(CLASS, "N", "Block", 0, 1, 1);
+        builder.type_def(CLASS, "N", "Data", 0, 1, 1);
+        builder.field(RVA_FIELD, "local", &[0x06, 0x11, coded(block, 0, 2) as u8]);
+        builder.row(0x0f, &[0, 8, block as u64]);
+        let rva = builder.data(&[1, 2, 3, 4, 5, 6, 7, 8, 9]) as u64;
+        builder.row(0x1d, &[rva, 1]);
+        let defining = builder.build();
+
+        // N.Other refers to N.Block and to a type that is not in the cache
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        builder.type_def(CLASS, "N", "Other", 0, 1, 1);
+        let block_ref = builder.type_ref("N", "Block");
+        let missing_ref = builder.type_ref("N", "Missing");
+        builder.field(RVA_FIELD, "number", &[0x06, 0x08]);
+        builder.field(RVA_FIELD, "block", &[0x06, 0x11, coded(block_ref, 1, 2) as u8]);
+        builder.field(RVA_FIELD, "missing", &[0x06, 0x11, coded(missing_ref, 1, 2) as u8]);
+        builder.field(0x0016, "plain", &[0x06, 0x08]);
+        let number_rva = builder.data(&[0x78, 0x56, 0x34, 0x12]) as u64;
+        let block_rva = builder.data(&[8, 7, 6, 5, 4, 3, 2, 1]) as u64;
+        builder.row(0x1d, &[number_rva, 1]);
+        builder.row(0x1d, &[block_rva, 2]);
+        builder.row(0x1d, &[block_rva, 3]);
+        let referencing = builder.build();
+
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&defining).unwrap());
+        cache.insert(Database::from_data(&referencing).unwrap());
+        let local = cache.find("N", "Data").unwrap().field_list().unwrap().next().unwrap();
+        assert_eq!(local.initial_data(&cache).unwrap(), Some(&[1u8, 2, 3, 4, 5, 6, 7, 8][..]));
+
+        let fields: Vec<_> = cache.find("N", "Other").unwrap().field_list().unwrap().collect();
+        assert_eq!(fields[0].initial_data(&cache).unwrap(), Some(&[0x78u8, 0x56, 0x34, 0x12][..]));
+        assert_eq!(fields[1].initial_data(&cache).unwrap(), Some(&[8u8, 7, 6, 5, 4, 3, 2, 1][..]));
+        assert!(fields[2].initial_data(&cache).is_err());
+        assert_eq!(fields[3].initial_data(&cache).unwrap(), None);
+    }
+
+    #[test]
+    fn test_interface_map() {
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        let ifoo = builder.type_def(INTERFACE, "N", "IFoo", 0, 1, 1);
+        let base = builder.type_def(CLASS, "N", "Base", 0, 1, 6);
+        let class = builder.type_def(CLASS, "N", "Impl", coded(base, 0, 2), 1, 8);
+
+        let void_no_args = [0x20, 0x00, 0x01];
+        let methods = [
+            (0x05c6, "Run"), (0x05c6, "Stop"), (0x05c6, "Jump"), (0x05c6, "Walk"), (0x05c6, "Swim"), // public abstract
+            (0x01c6, "Jump"), // public virtual
+            (0x01c4, "Swim"), // protected virtual
+            (0x01e6, "Run"), // public final virtual
+            (0x01e1, "N.IFoo.Stop"), // private final virtual
+            (0x01c1, "Walk") // private virtual
+        ];
+        for &(flags, name) in &methods {
+            builder.method(flags, name, &void_no_args);
+        }
+
+        builder.row(0x09, &[class as u64, coded(ifoo, 0, 2)]);
+        let swim = builder.string("Swim") as u64;
+        let signature = builder.blob(&void_no_args) as u64;
+        let base_swim = builder.row(0x0a, &[coded(base, 0, 3), swim, signature]);
+        // IFoo::Stop is implemented by a renamed private method, IFoo::Swim by a protected
+        // method of the base class that is referenced through a MemberRef
+        builder.row(0x19, &[class as u64, coded(9, 0, 1), coded(2, 0, 1)]);
+        builder.row(0x19, &[class as u64, coded(base_swim, 1, 1), coded(5, 0, 1)]);
+
+        let image = builder.build();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+        let map: Vec<_> = cache.find("N", "Impl").unwrap().interface_map(&cache).unwrap().into_iter()
+            .map(|m| (m.interface.to_string(), m.declaration.name().unwrap(), m.implementation.map(|i| i.to_string())))
+            .collect();
+        let implemented_by = |declaration, implementation: Option<&str>| ("class N.IFoo".to_string(), declaration, implementation.map(String::from));
+        assert_eq!(map, [
+            implemented_by("Run", Some("instance void N.Impl::Run()")),
+            implemented_by("Stop", Some("instance void N.Impl::N.IFoo.Stop()")),
+            implemented_by("Jump", Some("instance void N.Base::Jump()")),
+            implemented_by("Walk", None), // only a private method with the same name
+            implemented_by("Swim", Some("instance void N.Base::Swim()"))
+        ]);
+    }
+
+    #[test]
+    fn test_method_body() {
+        use crate::schema::{ExceptionClauseKind, TypeDefOrRef, StandAloneSignature};
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        builder.type_def(CLASS, "N", "Program", 0, 1, 1);
+        let exception = builder.type_ref("System", "Exception");
+        let locals = builder.blob(&[0x07, 0x01, 0x08]) as u64; // int32
+        builder.row(0x11, &[locals]);
+
+        // try { nop; leave.s 6 } catch System.Exception { pop; leave.s 6 } ret
+        let code = [0x00, 0xde, 0x03, 0x26, 0xde, 0x00, 0x2a];
+        let mut body = vec![0x1b, 0x30, 0x02, 0x00]; // fat header with more sections and init locals
+        body.extend_from_slice(&(code.len() as u32).to_le_bytes());
+        body.extend_from_slice(&0x1100_0001u32.to_le_bytes());
+        body.extend_from_slice(&code);
+        body.push(0); // the EH table is 4-byte aligned
+        body.extend_from_slice(&[0x01, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x03, 0x00, 0x03]);
+        body.extend_from_slice(&(0x0100_0000 | exception).to_le_bytes());
+        let rva = builder.data(&body) as u64;
+
+        let name = builder.string("Main") as u64;
+        let signature = builder.blob(&[0x00, 0x00, 0x01]) as u64;
+        builder.row(0x06, &[rva, 0, 0x0096, name, signature, 1]);
+        builder.method(0x05c6, "Abstract", &[0x20, 0x00, 0x01]);
+
+        let image = builder.build();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+        let methods: Vec<_> = cache.find("N", "Program").unwrap().method_list().unwrap().collect();
+        let body = methods[0].body().unwrap().unwrap();
+        assert_eq!(body.max_stack(), 2);
+        assert!(body.init_locals());
+        assert_eq!(body.code(), &code[..]);
+        match body.local_var_sig().unwrap().signature().unwrap() {
+            StandAloneSignature::LocalVar(sig) => assert_eq!(sig.locals().len(), 1),
+            StandAloneSignature::Method(_) => panic!("expected a LocalVarSig")
+        }
+
+        let clauses = body.exception_clauses();
+        assert_eq!(clauses.len(), 1);
+        assert_eq!((clauses[0].try_offset(), clauses[0].try_length()), (0, 3));
+        assert_eq!((clauses[0].handler_offset(), clauses[0].handler_length()), (3, 3));
+        match clauses[0].kind() {
+            ExceptionClauseKind::Catch(TypeDefOrRef::TypeRef(t)) => assert_eq!(t.type_name().unwrap(), "Exception"),
+            kind => panic!("expected a catch clause, got {:?}", kind)
+        }
+
+        assert!(methods[1].body().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_method_spec() {
+        use crate::schema::MethodDefOrRef;
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        builder.type_def(CLASS, "N", "Util", 0, 1, 1);
+        let identity = builder.method(0x0096, "Identity", &[0x10, 0x01, 0x01, 0x1e, 0x00, 0x1e, 0x00]); // !!0 <1>(!!0)
+        let int32 = builder.blob(&[0x0a, 0x01, 0x08]) as u64;
+        builder.row(0x2b, &[coded(identity, 0, 1), int32]);
+        builder.row(0x2b, &[0, 0]);
+
+        let image = builder.build();
+        let db = Database::from_data(&image).unwrap();
+        let specs = db.table::<crate::schema::MethodSpec>();
+        let spec = specs.get_row(0).unwrap();
+        assert!(matches!(spec.method().unwrap(), MethodDefOrRef::MethodDef(m) if m.name().unwrap() == "Identity"));
+        let args: Vec<_> = spec.instantiation().unwrap().generic_args().iter().map(|t| t.to_string()).collect();
+        assert_eq!(args, ["int32"]);
+        let generic = db.table::<crate::schema::MethodDef>().get_row(0).unwrap().signature().unwrap();
+        assert_eq!(generic.to_string(), "!!0<[1]>(!!0)");
+        assert_eq!(spec.instantiated_signature().unwrap().to_string(), "int32<[1]>(int32)");
+
+        let null = specs.get_row(1).unwrap();
+        assert!(null.method().is_err());
+        assert!(null.instantiation().is_err());
+    }
+
+    #[test]
+    fn test_member_ref_signature() {
+        use crate::schema::MemberRefSig;
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        let object = builder.type_ref("System", "Object");
+        let names = [builder.string("count") as u64, builder.string("Run") as u64];
+        let signatures = [builder.blob(&[0x06, 0x08]) as u64, builder.blob(&[0x20, 0x00, 0x01]) as u64];
+        for (&name, &signature) in names.iter().zip(signatures.iter()) {
+            builder.row(0x0a, &[coded(object, 1, 3), name, signature]);
+        }
+
+        let image = builder.build();
+        let db = Database::from_data(&image).unwrap();
+        let member_refs = db.table::<crate::schema::MemberRef>();
+        let field = member_refs.get_row(0).unwrap();
+        assert!(matches!(field.signature().unwrap(), MemberRefSig::Field(sig) if sig.to_string() == "int32"));
+        assert!(field.method_signature().is_err());
+        let method = member_refs.get_row(1).unwrap();
+        assert!(matches!(method.signature().unwrap(), MemberRefSig::Method(_)));
+        assert_eq!(method.method_signature().unwrap().to_string(), "instance void()");
+    }
+
+    #[test]
+    fn test_pinvoke_info() {
+        use crate::schema::{MemberForwarded, flags::{CharSet, PInvokeCallConv}};
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        builder.type_def(CLASS, "N", "Native", 0, 1, 1);
+        // public static pinvokeimpl, with a signature that does not matter here
+        let create_file = builder.method(0x2016, "CreateFileW", &[0x00, 0x00, 0x01]);
+        builder.method(0x0016, "Managed", &[0x00, 0x00, 0x01]);
+        let kernel32 = builder.string("kernel32.dll") as u64;
+        let kernel32 = builder.row(0x1a, &[kernel32]) as u64;
+        let import_name = builder.string("CreateFileW") as u64;
+        // ImplMap rows are sorted by the member, so the one with a NULL member comes first
+        builder.row(0x1c, &[0x0100, 0, import_name, kernel32]);
+        // SupportsLastError, CharSetUnicode, CallConvWinapi
+        builder.row(0x1c, &[0x0144, coded(create_file, 1, 1), import_name, kernel32]);
+
+        let image = builder.build();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+        let methods: Vec<_> = cache.find("N", "Native").unwrap().method_list().unwrap().collect();
+        assert!(methods[1].pinvoke_info().unwrap().is_none());
+
+        let info = methods[0].pinvoke_info().unwrap().unwrap();
+        assert_eq!(info.import_name().unwrap(), "CreateFileW");
+        assert_eq!(info.import_scope().unwrap().name().unwrap(), "kernel32.dll");
+        assert!(matches!(info.member_forwarded().unwrap(), MemberForwarded::MethodDef(m) if m == methods[0]));
+        let flags = info.flags().unwrap();
+        assert!(flags.supports_last_error());
+        assert!(!flags.no_mangle());
+        assert_eq!(flags.char_set(), CharSet::Unicode);
+        assert_eq!(flags.call_conv(), Some(PInvokeCallConv::Winapi));
+
+        let db = Database::from_data(&image).unwrap();
+        assert!(db.table::<crate::schema::ImplMap>().get_row(0).unwrap().member_forwarded().is_err());
+    }
+
+    #[test]
+    fn test_method_impl_null_columns() {
+        use crate::schema::{MethodImpl, MethodDefOrRef};
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        let class = builder.type_def(CLASS, "N", "Impl", 0, 1, 1) as u64;
+        builder.method(0x01c6, "Run", &[0x20, 0x00, 0x01]);
+        builder.row(0x19, &[class, coded(1, 0, 1), 0]);
+        builder.row(0x19, &[class, 0, coded(1, 0, 1)]);
+
+        let image = builder.build();
+        let db = Database::from_data(&image).unwrap();
+        let method_impls = db.table::<MethodImpl>();
+        let missing_declaration = method_impls.get_row(0).unwrap();
+        assert!(matches!(missing_declaration.method_body().unwrap(), MethodDefOrRef::MethodDef(_)));
+        assert!(missing_declaration.method_declaration().is_err());
+        assert!(method_impls.get_row(1).unwrap().method_body().is_err());
     }
 }
@@ -0,0 +1,142 @@
+use std::mem;
+use byteorder::ReadBytesExt;
+use crate::{Result, Cache};
+use crate::core::db::Database;
+use super::{NamedArg, uncompress_unsigned};
+use super::custom_attributes::read_string;
+
+// ECMA-335, II.22.11 (the values are those of System.Security.Permissions.SecurityAction)
+#[repr(u16)]
+#[derive(FromPrimitive, ToPrimitive)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SecurityAction {
+    Request = 1,
+    Demand = 2,
+    Assert = 3,
+    Deny = 4,
+    PermitOnly = 5,
+    LinkDemand = 6,
+    InheritanceDemand = 7,
+    RequestMinimum = 8,
+    RequestOptional = 9,
+    RequestRefuse = 10,
+    PrejitGrant = 11,
+    PrejitDeny = 12,
+    NonCasDemand = 13,
+    NonCasLinkDemand = 14,
+    NonCasInheritance = 15,
+}
+
+/// The permissions of a `DeclSecurity` row, either in the binary format introduced with
+/// .NET 2.0 or as a legacy XML string (ECMA-335, II.22.11).
+#[derive(Clone, Debug)]
+pub enum PermissionSet<'db> {
+    Binary(Vec<SecurityAttribute<'db>>),
+    Xml(String),
+}
+
+/// A security attribute of a binary permission set, e.g.
+/// `System.Security.Permissions.SecurityPermissionAttribute` with `UnmanagedCode = true`.
+#[derive(Clone, Debug)]
+pub struct SecurityAttribute<'db> {
+    /// The assembly-qualified name of the attribute type.
+    pub type_name: &'db str,
+    pub named_args: Vec<NamedArg<'db>>,
+}
+
+impl<'db> PermissionSet<'db> {
+    pub(crate) fn parse<'c: 'db>(cur: &mut &'db [u8], db: &'db Database<'db>, cache: &Cache<'c>) -> Result<PermissionSet<'db>> {
+        if cur.first() != Some(&b'.') {
+            return Ok(PermissionSet::Xml(parse_xml(cur)?));
+        }
+        cur.read_u8()?;
+
+        let count = uncompress_unsigned(cur)?;
+        let mut attributes = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let type_name = read_string(cur)?.ok_or(crate::DecodeError("Security attribute type name must not be NULL"))?;
+            let length = uncompress_unsigned(cur)? as usize;
+            if length > cur.len() {
+                return Err("Security attribute arguments exceed blob".into());
+            }
+            let (mut args, mut rest) = cur.split_at(length);
+            mem::swap(cur, &mut rest);
+
+            let named_args_count = uncompress_unsigned(&mut args)?;
+            let mut named_args = Vec::with_capacity(named_args_count as usize);
+            for _ in 0..named_args_count {
+                named_args.push(NamedArg::parse(&mut args, db, cache)?);
+            }
+            attributes.push(SecurityAttribute { type_name, named_args });
+        }
+        Ok(PermissionSet::Binary(attributes))
+    }
+}
+
+// The legacy format is the XML serialization of a PermissionSet, encoded in UTF-16
+fn parse_xml(blob: &[u8]) -> Result<String> {
+    let chunks = blob.chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return Err("XML permission set must be UTF-16 encoded".into());
+    }
+    let units: Vec<u16> = chunks.map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+    String::from_utf16(&units).map_err(|_| crate::DecodeError("Invalid UTF-16 in XML permission set"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cache, Database};
+    use crate::core::builder::{ImageBuilder, coded, CLASS};
+    use super::{PermissionSet, SecurityAction};
+
+    #[test]
+    fn test_parse_binary() {
+        let type_name = b"System.Security.Permissions.SecurityPermissionAttribute, mscorlib";
+        let mut blob = vec![b'.', 1, type_name.len() as u8];
+        blob.extend_from_slice(type_name);
+        let named_arg = b"\x01\x54\x02\x0dUnmanagedCode\x01"; // one property of type bool
+        blob.push(named_arg.len() as u8);
+        blob.extend_from_slice(named_arg);
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        let native = builder.type_def(CLASS, "N", "Native", 0, 1, 1);
+        let valid = builder.blob(&blob) as u64;
+        blob[2] += 1; // type name length exceeds the blob
+        let truncated = builder.blob(&blob[..type_name.len() + 3]) as u64;
+        // DeclSecurity rows are sorted by parent, so the one with a NULL parent comes first
+        builder.row(0x0e, &[2, 0, valid]);
+        builder.row(0x0e, &[2, coded(native, 0, 2), valid]);
+        builder.row(0x0e, &[6, coded(native, 0, 2), truncated]);
+        builder.row(0x0e, &[2, coded(native, 0, 2), 0]);
+
+        let image = builder.build();
+        let cache = Cache::new();
+        cache.insert(Database::from_data(&image).unwrap());
+        let decl_security: Vec<_> = cache.find("N", "Native").unwrap().decl_security().unwrap().collect();
+        assert_eq!(decl_security.len(), 3);
+        assert_eq!(decl_security[0].action().unwrap(), SecurityAction::Demand);
+        let attributes = match decl_security[0].permission_set(&cache).unwrap() {
+            PermissionSet::Binary(attributes) => attributes,
+            PermissionSet::Xml(_) => panic!("expected a binary permission set")
+        };
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(attributes[0].type_name.as_bytes(), &type_name[..]);
+        assert_eq!(attributes[0].named_args.len(), 1);
+        assert_eq!(attributes[0].named_args[0].to_string(), "property bool 'UnmanagedCode' = bool(true)");
+        assert!(decl_security[1].permission_set(&cache).is_err());
+        assert!(decl_security[2].permission_set(&cache).is_err());
+
+        let db = Database::from_data(&image).unwrap();
+        assert!(db.table::<crate::schema::DeclSecurity>().get_row(0).unwrap().parent().is_err());
+    }
+
+    #[test]
+    fn test_parse_xml() {
+        let xml = "<PermissionSet class=\"System.Security.PermissionSet\" version=\"1\"/>";
+        let blob: Vec<u8> = xml.encode_utf16().flat_map(|u| u.to_le_bytes().to_vec()).collect();
+        assert_eq!(super::parse_xml(&blob).unwrap(), xml);
+        assert!(super::parse_xml(&blob[1..]).is_err());
+        assert!(super::parse_xml(&[0x00, 0xd8]).is_err()); // unpaired surrogate
+    }
+}
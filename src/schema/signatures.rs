@@ -24,19 +24,37 @@ pub(crate) fn uncompress_unsigned(cursor: &mut &[u8]) -> Result<u32> {
     }
 }
 
-#[allow(dead_code, unused_variables)]
+// ECMA-335, II.23.2 (the sign bit is rotated into the least significant bit)
 pub(crate) fn uncompress_signed(cursor: &mut &[u8]) -> Result<i32> {
-    unimplemented!()
+    let width = match cursor.first() {
+        Some(first) if first & 0x80 == 0x00 => 7,
+        Some(first) if first & 0xc0 == 0x80 => 14,
+        _ => 29
+    };
+    let value = uncompress_unsigned(cursor)?;
+    let result = (value >> 1) as i32;
+    if value & 1 != 0 {
+        Ok(result - (1 << (width - 1)))
+    } else {
+        Ok(result)
+    }
 }
 
 
 #[allow(non_upper_case_globals, dead_code)]
 pub(crate) mod bits {
-    pub const CallingConvention_mask: u8 = 0x15; // 10101
+    pub const CallingConvention_mask: u8 = 0x0f;
     pub const DEFAULT: u8 = 0x00; // II.23.2.1
+    pub const C: u8 = 0x01; // II.23.2.3
+    pub const STDCALL: u8 = 0x02; // II.23.2.3
+    pub const THISCALL: u8 = 0x03; // II.23.2.3
+    pub const FASTCALL: u8 = 0x04; // II.23.2.3
     pub const VARARG: u8 = 0x05; // II.23.2.1
     pub const FIELD: u8 = 0x06; // II.23.2.4
+    pub const LOCAL_SIG: u8 = 0x07; // II.23.2.6
     pub const PROPERTY: u8 = 0x08; // II.23.2.5
+    pub const UNMANAGED: u8 = 0x09; // unmanaged function pointers (not in ECMA-335 6th edition)
+    pub const GENERICINST: u8 = 0x0a; // II.23.2.15
     //pub const PROPERTY: u8 = 0x28; // what about this one? (II.23.2.5)
     pub const GENERIC: u8 = 0x10; // II.23.2.1
 
@@ -87,8 +105,40 @@ pub(crate) mod bits {
     pub const ARG_ENUM: u8 = 0x55; // enum in custom attributes
 }
 
+// ECMA-335, II.15.3 and II.23.2.3
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CallingConvention {
+    Default,
+    VarArg,
+    C,
+    StdCall,
+    ThisCall,
+    FastCall,
+    Unmanaged,
+}
+
+impl CallingConvention {
+    // Returns `None` for the non-method kinds (FIELD, LOCAL_SIG, PROPERTY, GENERICINST)
+    fn from_bits(initial_byte: u8) -> Option<CallingConvention> {
+        Some(match initial_byte & bits::CallingConvention_mask {
+            bits::DEFAULT => CallingConvention::Default,
+            bits::VARARG => CallingConvention::VarArg,
+            bits::C => CallingConvention::C,
+            bits::STDCALL => CallingConvention::StdCall,
+            bits::THISCALL => CallingConvention::ThisCall,
+            bits::FASTCALL => CallingConvention::FastCall,
+            bits::UNMANAGED => CallingConvention::Unmanaged,
+            _ => return None
+        })
+    }
+
+    pub fn is_unmanaged(&self) -> bool {
+        !matches!(self, CallingConvention::Default | CallingConvention::VarArg)
+    }
+}
+
 // ECMA-335, II.23.2.1
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct MethodDefSig<'db> {
     m_initial_byte: u8,
     m_generic_param_count: u32,
@@ -99,29 +149,53 @@ pub struct MethodDefSig<'db> {
 impl<'db> MethodDefSig<'db> {
     pub(crate) fn parse(cur: &mut &'db [u8], db: &'db Database<'db>) -> Result<MethodDefSig<'db>> {
         let initial_byte = cur.read_u8()?;
-        assert!(initial_byte & bits::FIELD == 0 && initial_byte & bits::PROPERTY == 0);
+        match CallingConvention::from_bits(initial_byte) {
+            Some(CallingConvention::Default) | Some(CallingConvention::VarArg) => {},
+            _ => return Err("MethodDefSig blob requires DEFAULT or VARARG calling convention".into())
+        }
         let generic_param_count = if initial_byte & bits::GENERIC != 0 {
             uncompress_unsigned(cur)?
         } else {
             0
         };
 
+        let (sig, vararg_params) = MethodDefSig::parse_rest(cur, db, initial_byte, generic_param_count)?;
+        if !vararg_params.is_empty() {
+            return Err("MethodDefSig must not contain a SENTINEL".into());
+        }
+        Ok(sig)
+    }
+
+    // Parses everything after the calling convention and generic parameter count.
+    // Parameters following an ELEMENT_TYPE_SENTINEL are returned separately (II.23.2.2).
+    fn parse_rest(cur: &mut &'db [u8], db: &'db Database<'db>, initial_byte: u8, generic_param_count: u32) -> Result<(MethodDefSig<'db>, Vec<ParamSig<'db>>)> {
         let param_count = uncompress_unsigned(cur)?;
 
         let ret_type = RetType::parse(cur, db)?;
 
         let mut params = Vec::with_capacity(param_count as usize);
+        let mut vararg_params = Vec::new();
         
+        let mut seen_sentinel = false;
         for _ in 0..param_count {
-            params.push(ParamSig::parse(cur, db)?);
+            if cur.first() == Some(&bits::ELEMENT_TYPE_SENTINEL) {
+                if seen_sentinel { return Err("Duplicate SENTINEL in method signature".into()); }
+                cur.read_u8()?;
+                seen_sentinel = true;
+            }
+            if seen_sentinel {
+                vararg_params.push(ParamSig::parse(cur, db)?);
+            } else {
+                params.push(ParamSig::parse(cur, db)?);
+            }
         }
 
-        Ok(MethodDefSig {
+        Ok((MethodDefSig {
             m_initial_byte: initial_byte,
             m_generic_param_count: generic_param_count,
             m_ret_type: ret_type,
             m_params: params.into_boxed_slice()
-        })
+        }, vararg_params))
     }
 
     pub fn has_this(&self) -> bool {
@@ -137,7 +211,11 @@ impl<'db> MethodDefSig<'db> {
     }
 
     pub fn is_vararg(&self) -> bool {
-        self.m_initial_byte & bits::VARARG != 0
+        self.calling_convention() == CallingConvention::VarArg
+    }
+
+    pub fn calling_convention(&self) -> CallingConvention {
+        CallingConvention::from_bits(self.m_initial_byte).expect("calling convention is checked when parsing")
     }
 
     pub fn generic_param_count(&self) -> u32 {
@@ -151,9 +229,106 @@ impl<'db> MethodDefSig<'db> {
     pub fn params(&self) -> &[ParamSig<'db>] {
         &self.m_params
     }
+
+    /// Substitutes generic variables in the return type and all parameters (see `Type::substitute`).
+    ///
+    /// For a method of a closed generic type such as `IVector<string>`, pass the
+    /// type arguments of the `TypeSpec` as `type_args`.
+    pub fn instantiate(&self, type_args: &[Type<'db>], method_args: &[Type<'db>]) -> MethodDefSig<'db> {
+        MethodDefSig {
+            m_initial_byte: self.m_initial_byte,
+            m_generic_param_count: self.m_generic_param_count,
+            m_ret_type: self.m_ret_type.substitute(type_args, method_args),
+            m_params: self.m_params.iter().map(|p| p.substitute(type_args, method_args)).collect()
+        }
+    }
 }
 
-// TODO: impl Debug for MethodDefSig (s.a. II.15.3)
+impl<'db> fmt::Display for MethodDefSig<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_method_sig(self, &[], None, f)
+    }
+}
+
+// ECMA-335, II.23.2.2 (also used for StandAloneMethodSig, II.23.2.3)
+#[derive(Clone, PartialEq)]
+pub struct MethodRefSig<'db> {
+    m_sig: MethodDefSig<'db>,
+    m_vararg_params: Box<[ParamSig<'db>]>,
+}
+
+impl<'db> MethodRefSig<'db> {
+    pub(crate) fn parse(cur: &mut &'db [u8], db: &'db Database<'db>) -> Result<MethodRefSig<'db>> {
+        let initial_byte = cur.read_u8()?;
+        if CallingConvention::from_bits(initial_byte).is_none() {
+            return Err("MethodRefSig blob has unexpected calling convention".into());
+        }
+        let generic_param_count = if initial_byte & bits::GENERIC != 0 {
+            uncompress_unsigned(cur)?
+        } else {
+            0
+        };
+
+        let (sig, vararg_params) = MethodDefSig::parse_rest(cur, db, initial_byte, generic_param_count)?;
+        Ok(MethodRefSig {
+            m_sig: sig,
+            m_vararg_params: vararg_params.into_boxed_slice()
+        })
+    }
+
+    pub fn has_this(&self) -> bool {
+        self.m_sig.has_this()
+    }
+
+    pub fn explicit_this(&self) -> bool {
+        self.m_sig.explicit_this()
+    }
+
+    pub fn generic_param_count(&self) -> u32 {
+        self.m_sig.generic_param_count()
+    }
+
+    pub fn calling_convention(&self) -> CallingConvention {
+        self.m_sig.calling_convention()
+    }
+
+    pub fn is_vararg(&self) -> bool {
+        self.m_sig.is_vararg()
+    }
+
+    pub fn return_type(&self) -> &RetType<'db> {
+        self.m_sig.return_type()
+    }
+
+    /// The parameters declared by the callee, i.e. those before the SENTINEL.
+    pub fn params(&self) -> &[ParamSig<'db>] {
+        self.m_sig.params()
+    }
+
+    /// The extra arguments passed at a vararg call site, i.e. those after the SENTINEL.
+    pub fn vararg_params(&self) -> &[ParamSig<'db>] {
+        &self.m_vararg_params
+    }
+
+    /// The signature without the vararg parameters.
+    pub fn method_sig(&self) -> &MethodDefSig<'db> {
+        &self.m_sig
+    }
+
+    /// Substitutes generic variables in all parts of the signature (see `Type::substitute`).
+    pub fn instantiate(&self, type_args: &[Type<'db>], method_args: &[Type<'db>]) -> MethodRefSig<'db> {
+        MethodRefSig {
+            m_sig: self.m_sig.instantiate(type_args, method_args),
+            m_vararg_params: self.m_vararg_params.iter().map(|p| p.substitute(type_args, method_args)).collect()
+        }
+    }
+}
+
+impl<'db> fmt::Display for MethodRefSig<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_method_sig(&self.m_sig, &self.m_vararg_params, None, f)
+    }
+}
 
 
 // ECMA-335, II.23.2.4
@@ -184,15 +359,176 @@ impl<'db> FieldSig<'db> {
     pub fn custom_mod(&self) -> &[CustomMod<'db>] {
         &self.m_cmod[..]
     }
+
+    /// Substitutes generic variables in the field type (see `Type::substitute`).
+    pub fn instantiate(&self, type_args: &[Type<'db>], method_args: &[Type<'db>]) -> FieldSig<'db> {
+        FieldSig {
+            m_type: self.m_type.substitute(type_args, method_args),
+            m_cmod: self.m_cmod.clone()
+        }
+    }
+}
+
+impl<'db> fmt::Display for FieldSig<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.m_type)?;
+        fmt_custom_mods(&self.m_cmod, f)
+    }
+}
+
+// The signature blob of a MemberRef row (II.22.25)
+#[derive(Clone)]
+pub enum MemberRefSig<'db> {
+    Method(MethodRefSig<'db>),
+    Field(FieldSig<'db>),
+}
+
+impl<'db> MemberRefSig<'db> {
+    pub(crate) fn parse(cur: &mut &'db [u8], db: &'db Database<'db>) -> Result<MemberRefSig<'db>> {
+        match cur.first() {
+            Some(&bits::FIELD) => Ok(MemberRefSig::Field(FieldSig::parse(cur, db)?)),
+            Some(_) => Ok(MemberRefSig::Method(MethodRefSig::parse(cur, db)?)),
+            None => Err("Empty MemberRef signature blob".into())
+        }
+    }
+}
+
+// ECMA-335, II.23.2.6
+#[derive(Clone)]
+pub struct LocalVarSig<'db> {
+    m_locals: Box<[LocalVar<'db>]>,
+}
+
+impl<'db> LocalVarSig<'db> {
+    pub(crate) fn parse(cur: &mut &'db [u8], db: &'db Database) -> Result<LocalVarSig<'db>> {
+        let initial_byte = cur.read_u8()?;
+        if initial_byte != bits::LOCAL_SIG { return Err("LocalVarSig blob requires LOCAL_SIG".into()); }
+
+        let count = uncompress_unsigned(cur)?;
+        let mut locals = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            locals.push(LocalVar::parse(cur, db)?);
+        }
+
+        Ok(LocalVarSig {
+            m_locals: locals.into_boxed_slice()
+        })
+    }
+
+    pub fn locals(&self) -> &[LocalVar<'db>] {
+        &self.m_locals
+    }
+}
+
+#[derive(Clone)]
+pub struct LocalVar<'db> {
+    m_cmod: Vec<CustomMod<'db>>,
+    m_pinned: bool,
+    m_kind: ParamKind<'db>,
+}
+
+impl<'db> LocalVar<'db> {
+    fn parse(cur: &mut &'db [u8], db: &'db Database) -> Result<LocalVar<'db>> {
+        if cur.first() == Some(&bits::ELEMENT_TYPE_TYPEDBYREF) {
+            cur.read_u8()?;
+            return Ok(LocalVar {
+                m_cmod: Vec::new(),
+                m_pinned: false,
+                m_kind: ParamKind::TypedReference
+            });
+        }
+
+        // custom modifiers and the PINNED constraint may be interleaved
+        let mut cmod = Vec::new();
+        let mut pinned = false;
+        loop {
+            cmod.extend(CustomMod::parse(cur, db)?);
+            if cur.first() == Some(&bits::ELEMENT_TYPE_PINNED) {
+                cur.read_u8()?;
+                pinned = true;
+            } else {
+                break;
+            }
+        }
+
+        let kind = if cur.first() == Some(&bits::ELEMENT_TYPE_BYREF) {
+            cur.read_u8()?;
+            ParamKind::TypeByRef(Type::parse(cur, db)?)
+        } else {
+            ParamKind::Type(Type::parse(cur, db)?)
+        };
+
+        Ok(LocalVar {
+            m_cmod: cmod,
+            m_pinned: pinned,
+            m_kind: kind
+        })
+    }
+
+    pub fn custom_mod(&self) -> &[CustomMod<'db>] {
+        &self.m_cmod[..]
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.m_pinned
+    }
+
+    pub fn kind(&self) -> &ParamKind<'db> {
+        &self.m_kind
+    }
+}
+
+// ECMA-335, II.23.2.15
+#[derive(Clone)]
+pub struct MethodSpecSig<'db> {
+    m_generic_args: Box<[Type<'db>]>,
+}
+
+impl<'db> MethodSpecSig<'db> {
+    pub(crate) fn parse(cur: &mut &'db [u8], db: &'db Database) -> Result<MethodSpecSig<'db>> {
+        let initial_byte = cur.read_u8()?;
+        if initial_byte != bits::GENERICINST { return Err("MethodSpecSig blob requires GENERICINST".into()); }
+
+        let arg_count = uncompress_unsigned(cur)?;
+        let mut args = Vec::with_capacity(arg_count as usize);
+        for _ in 0..arg_count {
+            args.push(Type::parse(cur, db)?);
+        }
+
+        Ok(MethodSpecSig {
+            m_generic_args: args.into_boxed_slice()
+        })
+    }
+
+    pub fn generic_args(&self) -> &[Type<'db>] {
+        &self.m_generic_args
+    }
+}
+
+// The signature blob of a StandAloneSig row (II.22.36)
+#[derive(Clone)]
+pub enum StandAloneSignature<'db> {
+    LocalVar(LocalVarSig<'db>),
+    Method(MethodRefSig<'db>),
+}
+
+impl<'db> StandAloneSignature<'db> {
+    pub(crate) fn parse(cur: &mut &'db [u8], db: &'db Database<'db>) -> Result<StandAloneSignature<'db>> {
+        match cur.first() {
+            Some(&bits::LOCAL_SIG) => Ok(StandAloneSignature::LocalVar(LocalVarSig::parse(cur, db)?)),
+            Some(_) => Ok(StandAloneSignature::Method(MethodRefSig::parse(cur, db)?)),
+            None => Err("Empty StandAloneSig blob".into())
+        }
+    }
 }
 
 // TODO: this could also internally be Box<(Type, [CustomMod])>,
 //       where the tuple is dynamically sized, to have only one dynamic allocation
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct Array<'db> {
     m_type: Box<Type<'db>>,
-    m_cmod: Vec<CustomMod<'db>>
-    // TODO: optional ArrayShape
+    m_cmod: Vec<CustomMod<'db>>,
+    m_shape: Option<ArrayShape>, // None for SZARRAY
 }
 
 impl<'db> Array<'db> {
@@ -201,7 +537,18 @@ impl<'db> Array<'db> {
         let cmod = CustomMod::parse(cur, db)?;
         Ok(Array {
             m_type: Box::new(Type::parse(cur, db)?),
-            m_cmod: cmod
+            m_cmod: cmod,
+            m_shape: None
+        })
+    }
+
+    fn parse_array(cur: &mut &'db [u8], db: &'db Database) -> Result<Array<'db>> {
+        // ELEMENT_TYPE_ARRAY already consumed
+        let typ = Type::parse(cur, db)?;
+        Ok(Array {
+            m_type: Box::new(typ),
+            m_cmod: Vec::new(),
+            m_shape: Some(ArrayShape::parse(cur)?)
         })
     }
 
@@ -212,13 +559,90 @@ impl<'db> Array<'db> {
     pub fn custom_mod(&self) -> &[CustomMod<'db>] {
         &self.m_cmod[..]
     }
+
+    /// Returns the shape of a general array, or `None` for a single-dimensional, zero-based array (SZARRAY).
+    pub fn shape(&self) -> Option<&ArrayShape> {
+        self.m_shape.as_ref()
+    }
 }
 
+impl<'db> fmt::Display for Array<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.7.1
+        write!(f, "{}", self.m_type)?;
+        fmt_custom_mods(&self.m_cmod, f)?;
+        match self.m_shape {
+            None => write!(f, "[]"),
+            Some(ref shape) => write!(f, "[{}]", shape)
+        }
+    }
+}
 
 impl<'db> fmt::Debug for Array<'db> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // TODO: is this correct?
-        write!(f, "{:?}[]", self.m_type)
+        fmt::Display::fmt(self, f)
+    }
+}
+
+// ECMA-335, II.23.2.13
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArrayShape {
+    m_rank: u32,
+    m_sizes: Vec<u32>,
+    m_lo_bounds: Vec<i32>,
+}
+
+impl ArrayShape {
+    fn parse(cur: &mut &[u8]) -> Result<ArrayShape> {
+        let rank = uncompress_unsigned(cur)?;
+        let num_sizes = uncompress_unsigned(cur)?;
+        let mut sizes = Vec::with_capacity(num_sizes as usize);
+        for _ in 0..num_sizes {
+            sizes.push(uncompress_unsigned(cur)?);
+        }
+        let num_lo_bounds = uncompress_unsigned(cur)?;
+        let mut lo_bounds = Vec::with_capacity(num_lo_bounds as usize);
+        for _ in 0..num_lo_bounds {
+            lo_bounds.push(uncompress_signed(cur)?);
+        }
+        if sizes.len() > rank as usize || lo_bounds.len() > rank as usize {
+            return Err("ArrayShape has more sizes or lower bounds than dimensions".into());
+        }
+        Ok(ArrayShape {
+            m_rank: rank,
+            m_sizes: sizes,
+            m_lo_bounds: lo_bounds
+        })
+    }
+
+    pub fn rank(&self) -> u32 {
+        self.m_rank
+    }
+
+    /// The sizes of the first `sizes().len()` dimensions (the others are unspecified).
+    pub fn sizes(&self) -> &[u32] {
+        &self.m_sizes
+    }
+
+    /// The lower bounds of the first `lo_bounds().len()` dimensions (the others are unspecified).
+    pub fn lo_bounds(&self) -> &[i32] {
+        &self.m_lo_bounds
+    }
+}
+
+impl fmt::Display for ArrayShape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.14.2 (e.g. `0...,0...` for a two-dimensional array with lower bounds 0)
+        for i in 0..self.m_rank as usize {
+            if i > 0 { write!(f, ",")?; }
+            match (self.m_lo_bounds.get(i), self.m_sizes.get(i)) {
+                (Some(lo), Some(size)) => write!(f, "{}...{}", lo, *lo as i64 + *size as i64 - 1)?,
+                (Some(lo), None) => write!(f, "{}...", lo)?,
+                (None, Some(size)) => write!(f, "{}", size)?,
+                (None, None) => {}
+            }
+        }
+        Ok(())
     }
 }
 
@@ -263,6 +687,18 @@ pub enum PrimitiveType {
 }
 
 impl PrimitiveType {
+    /// The size in bytes, or `None` for the platform-dependent native integers (`I` and `U`).
+    pub fn size(&self) -> Option<u32> {
+        use PrimitiveType::*;
+        Some(match self {
+            Boolean | I1 | U1 => 1,
+            Char | I2 | U2 => 2,
+            I4 | U4 | R4 => 4,
+            I8 | U8 | R8 => 8,
+            I | U => return None
+        })
+    }
+
     pub(crate) fn parse_value<'db>(&self, cur: &mut &'db [u8]) -> Result<super::PrimitiveValue> {
         use super::PrimitiveValue::*;
         Ok(match self {
@@ -312,15 +748,15 @@ pub enum Type<'db> {
     Primitive(PrimitiveType),
     Array(Array<'db>), // for ARRAY and SZARRAY
     Ref(TypeTag, TypeDefOrRef<'db>, Option<Box<[Type<'db>]>>),
-    //FnPtr, // TODO
+    FnPtr(Box<MethodRefSig<'db>>),
     GenericVar(GenericVarScope, u32),
     Object,
-    //Ptr, // TODO
+    Ptr(Vec<CustomMod<'db>>, Option<Box<Type<'db>>>), // None for void*
     String,
 }
 
 impl<'db> Type<'db> {
-    fn parse(cur: &mut &'db [u8], db: &'db Database) -> Result<Type<'db>> {
+    fn parse(cur: &mut &'db [u8], db: &'db Database<'db>) -> Result<Type<'db>> {
         let element_type = uncompress_unsigned(cur)?;
         Ok(match element_type as u8 {
             bits::ELEMENT_TYPE_BOOLEAN => Type::Primitive(PrimitiveType::Boolean),
@@ -337,16 +773,24 @@ impl<'db> Type<'db> {
             bits::ELEMENT_TYPE_R8 => Type::Primitive(PrimitiveType::R8),
             bits::ELEMENT_TYPE_I => Type::Primitive(PrimitiveType::I),
             bits::ELEMENT_TYPE_U => Type::Primitive(PrimitiveType::U),
-            bits::ELEMENT_TYPE_ARRAY => unimplemented!(),
+            bits::ELEMENT_TYPE_ARRAY => Type::Array(Array::parse_array(cur, db)?),
             bits::ELEMENT_TYPE_CLASS => Type::Ref(TypeTag::Class, TypeDefOrRef::decode(uncompress_unsigned(cur)?, db)?.expect("Null type in Class Type"), None),
-            bits::ELEMENT_TYPE_FNPTR => unimplemented!(),
+            bits::ELEMENT_TYPE_FNPTR => Type::FnPtr(Box::new(MethodRefSig::parse(cur, db)?)),
             bits::ELEMENT_TYPE_GENERICINST => {
                 let (typetag, typ, args) = parse_generic_inst(cur, db)?;
                 Type::Ref(typetag, typ, Some(args))
             },
             bits::ELEMENT_TYPE_MVAR => Type::GenericVar(GenericVarScope::Method, uncompress_unsigned(cur)?),
             bits::ELEMENT_TYPE_OBJECT => Type::Object,
-            bits::ELEMENT_TYPE_PTR => unimplemented!(),
+            bits::ELEMENT_TYPE_PTR => {
+                let cmod = CustomMod::parse(cur, db)?;
+                if cur.first() == Some(&bits::ELEMENT_TYPE_VOID) {
+                    cur.read_u8()?;
+                    Type::Ptr(cmod, None)
+                } else {
+                    Type::Ptr(cmod, Some(Box::new(Type::parse(cur, db)?)))
+                }
+            },
             bits::ELEMENT_TYPE_STRING => Type::String,
             bits::ELEMENT_TYPE_SZARRAY => Type::Array(Array::parse_szarray(cur, db)?),
             bits::ELEMENT_TYPE_VALUETYPE => Type::Ref(TypeTag::ValueType, TypeDefOrRef::decode(uncompress_unsigned(cur)?, db)?.expect("Null type in ValueType Type"), None),
@@ -359,9 +803,47 @@ impl<'db> Type<'db> {
         match self {
             Type::Ref(_, _, Some(generic)) => generic.iter().any(|t| t.contains_generic_var()),
             Type::GenericVar(..) => true,
+            Type::Array(array) => array.elem_type().contains_generic_var(),
+            Type::Ptr(_, Some(t)) => t.contains_generic_var(),
+            Type::FnPtr(sig) => {
+                let ret = match sig.return_type().kind() {
+                    RetTypeKind::Type(t) | RetTypeKind::TypeByRef(t) => t.contains_generic_var(),
+                    _ => false
+                };
+                ret || sig.params().iter().chain(sig.vararg_params()).any(|p| match p.kind() {
+                    ParamKind::Type(t) | ParamKind::TypeByRef(t) => t.contains_generic_var(),
+                    _ => false
+                })
+            },
             _ => false
         }
     }
+
+    /// Replaces `!n` with `type_args[n]` and `!!n` with `method_args[n]`, descending into
+    /// arrays, pointers and generic instantiations. Generic variables without a
+    /// corresponding argument are left unchanged.
+    pub fn substitute(&self, type_args: &[Type<'db>], method_args: &[Type<'db>]) -> Type<'db> {
+        match self {
+            Type::GenericVar(scope, n) => {
+                let args = match scope {
+                    GenericVarScope::Type => type_args,
+                    GenericVarScope::Method => method_args
+                };
+                args.get(*n as usize).unwrap_or(self).clone()
+            },
+            Type::Array(array) => Type::Array(Array {
+                m_type: Box::new(array.m_type.substitute(type_args, method_args)),
+                m_cmod: array.m_cmod.clone(),
+                m_shape: array.m_shape.clone()
+            }),
+            Type::FnPtr(sig) => Type::FnPtr(Box::new(sig.instantiate(type_args, method_args))),
+            Type::Ref(tag, t, Some(generic)) => {
+                Type::Ref(*tag, t.clone(), Some(generic.iter().map(|g| g.substitute(type_args, method_args)).collect()))
+            },
+            Type::Ptr(cmod, Some(t)) => Type::Ptr(cmod.clone(), Some(Box::new(t.substitute(type_args, method_args)))),
+            _ => self.clone()
+        }
+    }
 }
 
 fn parse_generic_inst<'db>(cur: &mut &'db [u8], db: &'db Database) -> Result<(TypeTag, TypeDefOrRef<'db>, Box<[Type<'db>]>)> {
@@ -381,47 +863,164 @@ fn parse_generic_inst<'db>(cur: &mut &'db [u8], db: &'db Database) -> Result<(Ty
     Ok((typetag, typ, args.into_boxed_slice()))
 }
 
-fn fmt_typedeforref<'db>(t: &TypeDefOrRef<'db>, f: &mut fmt::Formatter) -> fmt::Result {
-    // ECMA-335, II.7.3
-    // FIXME: implement correctly and move to impl Debug for TypeRef ...
+// TypeDefs and TypeRefs are compared by their full names, including those of enclosing
+// types, so that references to the same type from different databases are considered equal.
+// Types whose names can not be decoded are unequal.
+fn same_typedeforref<'db>(a: &TypeDefOrRef<'db>, b: &TypeDefOrRef<'db>) -> bool {
+    match (a, b) {
+        (TypeDefOrRef::TypeSpec(a), TypeDefOrRef::TypeSpec(b)) => a == b,
+        (TypeDefOrRef::TypeSpec(_), _) | (_, TypeDefOrRef::TypeSpec(_)) => false,
+        _ => same_full_name(a.clone(), b.clone()).unwrap_or(false)
+    }
+}
+
+fn same_full_name<'db>(mut a: TypeDefOrRef<'db>, mut b: TypeDefOrRef<'db>) -> Result<bool> {
+    loop {
+        if namespace_and_name(&a)? != namespace_and_name(&b)? {
+            return Ok(false);
+        }
+        match (enclosing_type(&a)?, enclosing_type(&b)?) {
+            (Some(outer_a), Some(outer_b)) => {
+                a = outer_a;
+                b = outer_b;
+            },
+            (None, None) => return Ok(true),
+            _ => return Ok(false)
+        }
+    }
+}
+
+fn namespace_and_name<'db>(t: &TypeDefOrRef<'db>) -> Result<(&'db str, &'db str)> {
     match t {
-        TypeDefOrRef::TypeDef(_d) => write!(f, "TYPEDEF"), // TODO
-        TypeDefOrRef::TypeRef(r) => write!(f, "{}.{}", r.type_namespace().map_err(|_| fmt::Error)?, r.type_name().map_err(|_| fmt::Error)?),
-        TypeDefOrRef::TypeSpec(_s) => write!(f, "TYPESPEC"), // TODO
+        TypeDefOrRef::TypeDef(t) => Ok((t.type_namespace()?, t.type_name()?)),
+        TypeDefOrRef::TypeRef(t) => Ok((t.type_namespace()?, t.type_name()?)),
+        TypeDefOrRef::TypeSpec(_) => Err("TypeSpec has no namespace/name pair".into())
     }
 }
 
-impl<'db> fmt::Debug for Type<'db> {
+// Nested TypeRefs have the TypeRef of their enclosing type as resolution scope (ECMA-335, II.22.38)
+fn enclosing_type<'db>(t: &TypeDefOrRef<'db>) -> Result<Option<TypeDefOrRef<'db>>> {
+    Ok(match t {
+        TypeDefOrRef::TypeDef(t) => t.enclosing_type()?.map(TypeDefOrRef::TypeDef),
+        TypeDefOrRef::TypeRef(t) => match t.resolution_scope()? {
+            Some(super::ResolutionScope::TypeRef(outer)) => Some(TypeDefOrRef::TypeRef(outer)),
+            _ => None
+        },
+        TypeDefOrRef::TypeSpec(_) => None
+    })
+}
+
+impl<'db> PartialEq for Type<'db> {
+    fn eq(&self, other: &Type<'db>) -> bool {
+        use Type::*;
+        match (self, other) {
+            (Primitive(a), Primitive(b)) => a == b,
+            (Array(a), Array(b)) => a == b,
+            (Ref(tag_a, a, generic_a), Ref(tag_b, b, generic_b)) => tag_a == tag_b && same_typedeforref(a, b) && generic_a == generic_b,
+            (GenericVar(scope_a, a), GenericVar(scope_b, b)) => scope_a == scope_b && a == b,
+            (Object, Object) | (String, String) => true,
+            (Ptr(cmod_a, a), Ptr(cmod_b, b)) => cmod_a == cmod_b && a == b,
+            (FnPtr(a), FnPtr(b)) => a == b,
+            _ => false
+        }
+    }
+}
+
+impl<'db> fmt::Display for Type<'db> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // ECMA-335, II.7.1
         use Type::*;
         match *self {
             Primitive(prim) => write!(f, "{:?}", prim),
-            Array(ref array) => write!(f, "{:?}[]", array.elem_type()), // TODO: array shape?
+            Array(ref array) => write!(f, "{}", array),
+            Ref(_, TypeDefOrRef::TypeSpec(ref t), _) => write!(f, "{}", t),
             Ref(tag, ref t, ref generic) => {
-                write!(f, "{:?} ", tag)?;
-                fmt_typedeforref(t, f)?;
+                write!(f, "{:?} {}", tag, t)?;
                 if let Some(g) = generic {
-                    write!(f, "<")?;
-                    let mut first = true;
-                    for arg in g.iter() {
-                        if !first { write!(f, ", ")?; }
-                        first = false;
-                        write!(f, "{:?}", arg)?;
-                    }
-                    write!(f, ">")?;
+                    fmt_generic_args(g, f)?;
                 }
                 Ok(())
             }
+            FnPtr(ref sig) => {
+                write!(f, "method ")?;
+                fmt_method_sig(sig.method_sig(), sig.vararg_params(), Some(&"*"), f)
+            }
             GenericVar(GenericVarScope::Type, n) => write!(f, "!{}", n),
             GenericVar(GenericVarScope::Method, n) => write!(f, "!!{}", n),
             Object => write!(f, "object"),
+            Ptr(ref cmod, None) => {
+                write!(f, "void")?;
+                fmt_custom_mods(cmod, f)?;
+                write!(f, "*")
+            },
+            Ptr(ref cmod, Some(ref t)) => {
+                write!(f, "{}", t)?;
+                fmt_custom_mods(cmod, f)?;
+                write!(f, "*")
+            },
             String => write!(f, "string")
         }
     }
 }
 
-#[derive(Clone)]
+impl<'db> fmt::Debug for Type<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+fn fmt_generic_args<'db>(args: &[Type<'db>], f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "<")?;
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 { write!(f, ", ")?; }
+        write!(f, "{}", arg)?;
+    }
+    write!(f, ">")
+}
+
+fn fmt_custom_mods<'db>(cmods: &[CustomMod<'db>], f: &mut fmt::Formatter) -> fmt::Result {
+    for cmod in cmods {
+        write!(f, " {}", cmod)?;
+    }
+    Ok(())
+}
+
+// ECMA-335, II.15.3 (`name` is omitted for standalone signatures)
+pub(crate) fn fmt_method_sig<'db>(sig: &MethodDefSig<'db>, vararg_params: &[ParamSig<'db>], name: Option<&dyn fmt::Display>, f: &mut fmt::Formatter) -> fmt::Result {
+    if sig.has_this() { write!(f, "instance ")?; }
+    if sig.explicit_this() { write!(f, "explicit ")?; }
+    match sig.calling_convention() {
+        CallingConvention::Default => {},
+        CallingConvention::VarArg => write!(f, "vararg ")?,
+        CallingConvention::C => write!(f, "unmanaged cdecl ")?,
+        CallingConvention::StdCall => write!(f, "unmanaged stdcall ")?,
+        CallingConvention::ThisCall => write!(f, "unmanaged thiscall ")?,
+        CallingConvention::FastCall => write!(f, "unmanaged fastcall ")?,
+        CallingConvention::Unmanaged => write!(f, "unmanaged ")?,
+    }
+    write!(f, "{}", sig.return_type())?;
+    if let Some(name) = name {
+        write!(f, " {}", name)?;
+    }
+    if sig.is_generic() {
+        write!(f, "<[{}]>", sig.generic_param_count())?;
+    }
+    write!(f, "(")?;
+    for (i, param) in sig.params().iter().enumerate() {
+        if i > 0 { write!(f, ", ")?; }
+        write!(f, "{}", param)?;
+    }
+    if !vararg_params.is_empty() {
+        if !sig.params().is_empty() { write!(f, ", ")?; }
+        write!(f, "...")?;
+        for param in vararg_params {
+            write!(f, ", {}", param)?;
+        }
+    }
+    write!(f, ")")
+}
+
+#[derive(Clone, PartialEq)]
 pub enum RetTypeKind<'db> {
     Void,
     Type(Type<'db>),
@@ -435,8 +1034,8 @@ impl<'db> fmt::Debug for RetTypeKind<'db> {
         // ECMA-335, II.7.1
         match *self {
             Void => write!(f, "void")?,
-            Type(ref t) => write!(f, "{:?}", t)?,
-            TypeByRef(ref t) => write!(f, "{:?}&", t)?,
+            Type(ref t) => write!(f, "{}", t)?,
+            TypeByRef(ref t) => write!(f, "{}&", t)?,
             TypedReference => write!(f, "typedref")?
         }
         Ok(())
@@ -444,7 +1043,7 @@ impl<'db> fmt::Debug for RetTypeKind<'db> {
 }
 
 // ECMA-335, II.23.2.11
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct RetType<'db> {
     m_cmod: Vec<CustomMod<'db>>,
     m_kind: RetTypeKind<'db>,
@@ -479,9 +1078,28 @@ impl<'db> RetType<'db> {
     pub fn kind(&self) -> &RetTypeKind<'db> {
         &self.m_kind
     }
+
+    fn substitute(&self, type_args: &[Type<'db>], method_args: &[Type<'db>]) -> RetType<'db> {
+        let kind = match self.m_kind {
+            RetTypeKind::Type(ref t) => RetTypeKind::Type(t.substitute(type_args, method_args)),
+            RetTypeKind::TypeByRef(ref t) => RetTypeKind::TypeByRef(t.substitute(type_args, method_args)),
+            ref other => other.clone()
+        };
+        RetType {
+            m_cmod: self.m_cmod.clone(),
+            m_kind: kind
+        }
+    }
 }
 
-#[derive(Clone)]
+impl<'db> fmt::Display for RetType<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.m_kind)?;
+        fmt_custom_mods(&self.m_cmod, f)
+    }
+}
+
+#[derive(Clone, PartialEq)]
 pub enum ParamKind<'db> {
     Type(Type<'db>),
     TypeByRef(Type<'db>),
@@ -502,7 +1120,7 @@ impl<'db> fmt::Debug for ParamKind<'db> {
 }
 
 // ECMA-335, II.23.2.10 (renamed to prevent name conflict with Param table row)
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct ParamSig<'db> {
     m_cmod: Vec<CustomMod<'db>>,
     m_kind: ParamKind<'db>,
@@ -536,6 +1154,30 @@ impl<'db> ParamSig<'db> {
     pub fn kind(&self) -> &ParamKind<'db> {
         &self.m_kind
     }
+
+    fn substitute(&self, type_args: &[Type<'db>], method_args: &[Type<'db>]) -> ParamSig<'db> {
+        let kind = match self.m_kind {
+            ParamKind::Type(ref t) => ParamKind::Type(t.substitute(type_args, method_args)),
+            ParamKind::TypeByRef(ref t) => ParamKind::TypeByRef(t.substitute(type_args, method_args)),
+            ParamKind::TypedReference => ParamKind::TypedReference
+        };
+        ParamSig {
+            m_cmod: self.m_cmod.clone(),
+            m_kind: kind
+        }
+    }
+}
+
+impl<'db> fmt::Display for ParamSig<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.7.1
+        match self.m_kind {
+            ParamKind::Type(ref t) => write!(f, "{}", t)?,
+            ParamKind::TypeByRef(ref t) => write!(f, "{}&", t)?,
+            ParamKind::TypedReference => write!(f, "typedref")?
+        }
+        fmt_custom_mods(&self.m_cmod, f)
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -551,6 +1193,12 @@ pub struct CustomMod<'db> {
     m_type: TypeDefOrRef<'db>
 }
 
+impl<'db> PartialEq for CustomMod<'db> {
+    fn eq(&self, other: &CustomMod<'db>) -> bool {
+        self.m_tag == other.m_tag && same_typedeforref(&self.m_type, &other.m_type)
+    }
+}
+
 impl<'db> CustomMod<'db> {
     fn parse(cur: &mut &'db [u8], db: &'db Database) -> Result<Vec<CustomMod<'db>>> {
         let mut result = Vec::new();
@@ -584,6 +1232,16 @@ impl<'db> CustomMod<'db> {
     }
 }
 
+impl<'db> fmt::Display for CustomMod<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // ECMA-335, II.7.1.1
+        match self.m_tag {
+            CustomModTag::Optional => write!(f, "modopt({})", self.m_type),
+            CustomModTag::Required => write!(f, "modreq({})", self.m_type)
+        }
+    }
+}
+
 // ECMA-335, II.23.2.14 (renamed to prevent name clash with TypeSpec table row)
 #[derive(Clone)]
 pub enum TypeSpecSig<'db> {
@@ -607,26 +1265,24 @@ impl<'db> TypeSpecSig<'db> {
     }
 }
 
-impl<'db> fmt::Debug for TypeSpecSig<'db> {
+impl<'db> fmt::Display for TypeSpecSig<'db> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // compare impl Debug for Type
+        // compare impl Display for Type
         match *self {
             TypeSpecSig::GenericInst(tag, ref t, ref generic) => {
-                write!(f, "{:?} ", tag)?;
-                fmt_typedeforref(t, f)?;
-                write!(f, "<")?;
-                let mut first = true;
-                for arg in generic.iter() {
-                    if !first { write!(f, ", ")?; }
-                    first = false;
-                    write!(f, "{:?}", arg)?;
-                }
-                write!(f, ">")
+                write!(f, "{:?} {}", tag, t)?;
+                fmt_generic_args(generic, f)
             }
         }
     }
 }
 
+impl<'db> fmt::Debug for TypeSpecSig<'db> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -646,19 +1302,193 @@ mod tests {
         assert!(uncompress_unsigned(&[]).is_err());
     }
 
-    // fn uncompress_signed(mut data: &[u8]) -> crate::Result<u32> {
-    //     super::uncompress_signed(&mut data)
-    // }
-
-    // #[test]
-    // fn test_uncompress_signed() {
-    //     assert_eq!(uncompress_signed(&[0x06]).unwrap(), 3);
-    //     assert_eq!(uncompress_signed(&[0x7B]).unwrap(), -3);
-    //     assert_eq!(uncompress_signed(&[0x80, 0x80]).unwrap(), 64);
-    //     assert_eq!(uncompress_signed(&[0x01]).unwrap(), -64);
-    //     assert_eq!(uncompress_signed(&[0xC0, 0x00, 0x40, 0x00]).unwrap(), 8192);
-    //     assert_eq!(uncompress_signed(&[0x80, 0x01]).unwrap(), -8192);
-    //     assert_eq!(uncompress_signed(&[0xDF, 0xFF, 0xFF, 0xFF]).unwrap(), 268435455);
-    //     assert_eq!(uncompress_signed(&[0xC0, 0x00, 0x00, 0x01]).unwrap(), -268435456);
-    // }
+    #[test]
+    fn test_local_var_sig() {
+        use crate::core::builder::ImageBuilder;
+        use crate::{Database, schema};
+        use super::{StandAloneSignature, ParamKind};
+
+        let mut builder = ImageBuilder::new();
+        // int32, string& pinned, typedref
+        let locals = builder.blob(&[0x07, 0x03, 0x08, 0x45, 0x10, 0x0e, 0x16]);
+        builder.row(0x11, &[locals as u64]);
+        builder.row(0x11, &[0]);
+        let image = builder.build();
+        let db = Database::from_data(&image).unwrap();
+        let mut sigs = db.table::<schema::StandAloneSig>().iter();
+
+        let locals = match sigs.next().unwrap().signature().unwrap() {
+            StandAloneSignature::LocalVar(sig) => sig,
+            StandAloneSignature::Method(_) => panic!("expected a LocalVarSig")
+        };
+        let locals = locals.locals();
+        assert_eq!(locals.len(), 3);
+        assert!(!locals[0].is_pinned());
+        assert!(matches!(locals[0].kind(), ParamKind::Type(t) if format!("{:?}", t) == "int32"));
+        assert!(locals[1].is_pinned());
+        assert!(matches!(locals[1].kind(), ParamKind::TypeByRef(t) if format!("{:?}", t) == "string"));
+        assert!(matches!(locals[2].kind(), ParamKind::TypedReference));
+
+        // a NULL signature blob is an error rather than a panic
+        assert!(sigs.next().unwrap().signature().is_err());
+    }
+
+    #[test]
+    fn test_vararg_sig() {
+        use crate::core::builder::ImageBuilder;
+        use crate::{Database, schema};
+        use super::{StandAloneSignature, CallingConvention, ParamKind};
+
+        let mut builder = ImageBuilder::new();
+        // vararg void(int32, ..., string, int64)
+        let call_site = builder.blob(&[0x05, 0x03, 0x01, 0x08, 0x41, 0x0e, 0x0a]);
+        let duplicate_sentinel = builder.blob(&[0x05, 0x02, 0x01, 0x41, 0x08, 0x41, 0x0e]);
+        builder.row(0x11, &[call_site as u64]);
+        builder.row(0x11, &[duplicate_sentinel as u64]);
+        // a MethodDefSig must not contain a SENTINEL
+        builder.row(0x06, &[0, 0, 0, 0, call_site as u64, 1]);
+        let image = builder.build();
+        let db = Database::from_data(&image).unwrap();
+        let mut sigs = db.table::<schema::StandAloneSig>().iter();
+
+        let sig = match sigs.next().unwrap().signature().unwrap() {
+            StandAloneSignature::Method(sig) => sig,
+            StandAloneSignature::LocalVar(_) => panic!("expected a method signature")
+        };
+        assert!(sig.is_vararg());
+        assert_eq!(sig.calling_convention(), CallingConvention::VarArg);
+        assert_eq!(sig.params().len(), 1);
+        assert!(matches!(sig.params()[0].kind(), ParamKind::Type(t) if format!("{:?}", t) == "int32"));
+        let varargs: Vec<_> = sig.vararg_params().iter().map(|p| match p.kind() {
+            ParamKind::Type(t) => format!("{:?}", t),
+            _ => panic!("expected a plain type")
+        }).collect();
+        assert_eq!(varargs, ["string", "int64"]);
+
+        assert!(sigs.next().unwrap().signature().is_err());
+        assert!(db.table::<schema::MethodDef>().iter().next().unwrap().signature().is_err());
+    }
+
+    #[test]
+    fn test_calling_convention() {
+        use super::CallingConvention;
+        assert_eq!(CallingConvention::from_bits(0x20), Some(CallingConvention::Default));
+        assert_eq!(CallingConvention::from_bits(0x25), Some(CallingConvention::VarArg));
+        assert_eq!(CallingConvention::from_bits(0x01), Some(CallingConvention::C));
+        assert_eq!(CallingConvention::from_bits(0x02), Some(CallingConvention::StdCall));
+        assert_eq!(CallingConvention::from_bits(0x23), Some(CallingConvention::ThisCall));
+        assert_eq!(CallingConvention::from_bits(0x04), Some(CallingConvention::FastCall));
+        assert_eq!(CallingConvention::from_bits(0x09), Some(CallingConvention::Unmanaged));
+        assert_eq!(CallingConvention::from_bits(0x30), Some(CallingConvention::Default));
+        assert_eq!(CallingConvention::from_bits(0x06), None);
+        assert_eq!(CallingConvention::from_bits(0x07), None);
+        assert_eq!(CallingConvention::from_bits(0x28), None);
+        assert_eq!(CallingConvention::from_bits(0x0a), None);
+    }
+
+    #[test]
+    fn test_substitute() {
+        use super::{Type, Array, PrimitiveType, GenericVarScope};
+        let type_args = [Type::String];
+        let method_args = [Type::Primitive(PrimitiveType::I4)];
+
+        let t = Type::GenericVar(GenericVarScope::Type, 0).substitute(&type_args, &method_args);
+        assert_eq!(format!("{:?}", t), "string");
+        let t = Type::GenericVar(GenericVarScope::Method, 0).substitute(&type_args, &method_args);
+        assert_eq!(format!("{:?}", t), "int32");
+        let t = Type::GenericVar(GenericVarScope::Method, 1).substitute(&type_args, &method_args);
+        assert_eq!(format!("{:?}", t), "!!1");
+
+        let array = Type::Array(Array { m_type: Box::new(Type::GenericVar(GenericVarScope::Method, 0)), m_cmod: Vec::new(), m_shape: None });
+        let t = array.substitute(&type_args, &method_args);
+        assert_eq!(format!("{:?}", t), "int32[]");
+        assert!(!t.contains_generic_var());
+
+        let param = super::ParamSig { m_cmod: Vec::new(), m_kind: super::ParamKind::TypeByRef(Type::GenericVar(GenericVarScope::Type, 0)) };
+        let p = param.substitute(&type_args, &method_args);
+        assert_eq!(format!("{:?}", p.kind()), "byref string");
+    }
+
+    #[test]
+    fn test_display() {
+        use super::{Type, Array, ArrayShape, PrimitiveType, GenericVarScope, MethodDefSig, RetType, RetTypeKind, ParamSig, ParamKind};
+        let shape = ArrayShape::parse(&mut &[0x02, 0x00, 0x02, 0x00, 0x00][..]).unwrap();
+        assert_eq!(shape.rank(), 2);
+        assert_eq!(shape.lo_bounds(), &[0, 0]);
+        let array = Type::Array(Array { m_type: Box::new(Type::Primitive(PrimitiveType::I4)), m_cmod: Vec::new(), m_shape: Some(shape) });
+        assert_eq!(array.to_string(), "int32[0...,0...]");
+        let shape = ArrayShape::parse(&mut &[0x03, 0x02, 0x04, 0x05, 0x01, 0x7D][..]).unwrap();
+        assert_eq!(shape.to_string(), "-2...1,5,");
+        assert!(ArrayShape::parse(&mut &[0x01, 0x02, 0x04, 0x05, 0x00][..]).is_err());
+
+        let sig = MethodDefSig {
+            m_initial_byte: super::bits::HASTHIS | super::bits::GENERIC,
+            m_generic_param_count: 1,
+            m_ret_type: RetType { m_cmod: Vec::new(), m_kind: RetTypeKind::Void },
+            m_params: vec![
+                ParamSig { m_cmod: Vec::new(), m_kind: ParamKind::TypeByRef(Type::GenericVar(GenericVarScope::Method, 0)) },
+                ParamSig { m_cmod: Vec::new(), m_kind: ParamKind::Type(Type::Ptr(Vec::new(), None)) }
+            ].into_boxed_slice()
+        };
+        assert_eq!(sig.to_string(), "instance void<[1]>(!!0&, void*)");
+    }
+
+    fn uncompress_signed(mut data: &[u8]) -> crate::Result<i32> {
+        super::uncompress_signed(&mut data)
+    }
+
+    #[test]
+    fn test_uncompress_signed() {
+        assert_eq!(uncompress_signed(&[0x06]).unwrap(), 3);
+        assert_eq!(uncompress_signed(&[0x7B]).unwrap(), -3);
+        assert_eq!(uncompress_signed(&[0x80, 0x80]).unwrap(), 64);
+        assert_eq!(uncompress_signed(&[0x01]).unwrap(), -64);
+        assert_eq!(uncompress_signed(&[0xC0, 0x00, 0x40, 0x00]).unwrap(), 8192);
+        assert_eq!(uncompress_signed(&[0x80, 0x01]).unwrap(), -8192);
+        assert_eq!(uncompress_signed(&[0xDF, 0xFF, 0xFF, 0xFE]).unwrap(), 268435455);
+        assert_eq!(uncompress_signed(&[0xC0, 0x00, 0x00, 0x01]).unwrap(), -268435456);
+        assert!(uncompress_signed(&[]).is_err());
+    }
+
+    #[test]
+    fn test_nested_type_eq() {
+        use crate::core::builder::{ImageBuilder, INTERFACE, CLASS, coded};
+        use crate::{Database, schema};
+        use super::{Type, TypeTag, TypeDefOrRef};
+
+        let mut builder = ImageBuilder::new();
+        builder.module_type();
+        let outer1 = builder.type_def(CLASS, "N", "Outer1", 0, 1, 1);
+        let outer2 = builder.type_def(CLASS, "N", "Outer2", 0, 1, 1);
+        let inner1 = builder.type_def(INTERFACE, "", "IFoo", 0, 1, 1);
+        let inner2 = builder.type_def(INTERFACE, "", "IFoo", 0, 1, 1);
+        let broken = builder.type_def(INTERFACE, "", "IFoo", 0, 1, 1);
+        builder.row(0x29, &[inner1 as u64, outer1 as u64]);
+        builder.row(0x29, &[inner2 as u64, outer2 as u64]);
+        builder.row(0x29, &[broken as u64, 99]);
+        let outer_ref = builder.type_ref("N", "Outer1");
+        let name = builder.string("IFoo") as u64;
+        let namespace = builder.string("") as u64;
+        builder.row(0x01, &[coded(outer_ref, 3, 2), name, namespace]);
+        let image = builder.build();
+        let db = Database::from_data(&image).unwrap();
+
+        let type_defs = db.table::<schema::TypeDef>();
+        let type_refs = db.table::<schema::TypeRef>();
+        let class = |t| Type::Ref(TypeTag::Class, t, None);
+        let inner1 = class(TypeDefOrRef::TypeDef(type_defs.get_row(inner1 - 1).unwrap()));
+        let inner2 = class(TypeDefOrRef::TypeDef(type_defs.get_row(inner2 - 1).unwrap()));
+        let inner_ref = class(TypeDefOrRef::TypeRef(type_refs.get_row(1).unwrap()));
+        let outer_ref = class(TypeDefOrRef::TypeRef(type_refs.get_row(outer_ref - 1).unwrap()));
+        let broken = class(TypeDefOrRef::TypeDef(type_defs.get_row(broken - 1).unwrap()));
+
+        // Outer1/IFoo and Outer2/IFoo only differ in their enclosing type
+        assert!(inner1 != inner2);
+        assert!(inner1 == inner_ref);
+        assert!(inner2 != inner_ref);
+        assert!(inner_ref != outer_ref);
+        // an enclosing type that can not be decoded compares unequal instead of panicking
+        assert!(broken != inner1);
+        assert!(broken != broken.clone());
+    }
 }